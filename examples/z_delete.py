@@ -41,6 +41,9 @@ parser.add_argument('--key', '-k', dest='key',
                     default='demo/example/zenoh-python-put',
                     type=str,
                     help='The key expression matching resources to delete.')
+parser.add_argument('--allow-wildcard', dest='allow_wildcard',
+                    action='store_true',
+                    help='Allow deleting every resource matched by a key expression containing wildcards.')
 parser.add_argument('--config', '-c', dest='config',
                     metavar='FILE',
                     type=str,
@@ -65,6 +68,6 @@ print("Opening session...")
 session = zenoh.open(conf)
 
 print("Deleting resources matching '{}'...".format(key))
-session.delete(key)
+session.delete(key, allow_wildcard=args.allow_wildcard)
 
 session.close()
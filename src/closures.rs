@@ -13,7 +13,10 @@
 //
 use std::{
     convert::TryFrom,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use pyo3::{
@@ -45,6 +48,7 @@ impl<T> CallbackUnwrap for PyResult<T> {
 pub(crate) struct PyClosure<I> {
     pub(crate) pycall: Py<PyAny>,
     pub(crate) drop: Option<Py<PyAny>>,
+    counter: Option<Arc<AtomicU64>>,
     _marker: std::marker::PhantomData<I>,
 }
 impl<I> TryFrom<&PyAny> for PyClosure<I> {
@@ -68,20 +72,42 @@ impl<I> TryFrom<&PyAny> for PyClosure<I> {
             Ok(PyClosure {
                 pycall,
                 drop,
+                counter: None,
                 _marker: std::marker::PhantomData,
             })
         })
     }
 }
+impl<I> PyClosure<I> {
+    /// Makes the closure increment `counter` every time it is invoked by Zenoh.
+    pub(crate) fn counted(mut self, counter: Arc<AtomicU64>) -> Self {
+        self.counter = Some(counter);
+        self
+    }
+}
 impl<I: IntoPy<Py<PyTuple>>> PyClosure<I> {
     pub fn call(&self, args: I) -> PyResult<PyObject> {
         Python::with_gil(|py| self.pycall.call1(py, args))
     }
 }
+impl<I> PyClosure<(I,)>
+where
+    (I,): IntoPy<Py<PyTuple>>,
+{
+    /// Passes `arg` to the Python callback as Zenoh would, counting the call if the closure is counted.
+    pub(crate) fn invoke<T: Into<I>>(&self, arg: T) {
+        if let Some(counter) = &self.counter {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        self.call((arg.into(),)).cb_unwrap();
+    }
+}
 impl<I> Drop for PyClosure<I> {
     fn drop(&mut self) {
         if let Some(drop) = self.drop.take() {
-            Python::with_gil(|py| drop.call0(py)).unwrap();
+            if let Err(e) = Python::with_gil(|py| drop.call0(py)) {
+                log::error!("Exception thrown in callback's drop: {}", e);
+            }
         }
     }
 }
@@ -94,93 +120,204 @@ where
     type Receiver = ();
 
     fn into_cb_receiver_pair(self) -> (zenoh::handlers::Callback<'static, T>, Self::Receiver) {
-        (
-            Arc::new(move |reply| {
-                self.call((reply.into(),)).cb_unwrap();
-            }),
-            (),
-        )
+        (Arc::new(move |reply| self.invoke(reply)), ())
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OnFull {
+    Block,
+    DropLatest,
+    DropOldest,
+}
+
 #[pyclass(subclass)]
 pub struct _Queue {
     send: Mutex<Option<flume::Sender<PyObject>>>,
     recv: flume::Receiver<PyObject>,
+    on_full: OnFull,
+    dropped: AtomicUsize,
 }
 #[pymethods]
 impl _Queue {
     #[new]
-    pub fn pynew(bound: Option<usize>) -> Self {
-        let (send, recv) = match bound {
+    pub fn pynew(capacity: Option<usize>, on_full: Option<&str>) -> PyResult<Self> {
+        let on_full = match on_full.unwrap_or("block") {
+            "block" => OnFull::Block,
+            "drop_latest" => OnFull::DropLatest,
+            "drop_oldest" => OnFull::DropOldest,
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown `on_full` policy `{}`: must be one of `block`, `drop_latest` or `drop_oldest`",
+                    other
+                )))
+            }
+        };
+        if on_full != OnFull::Block && capacity == Some(0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Drop policies require a non-zero `capacity`",
+            ));
+        }
+        let (send, recv) = match capacity {
             None => flume::unbounded(),
-            Some(bound) => flume::bounded(bound),
+            Some(capacity) => flume::bounded(capacity),
         };
-        Self {
+        Ok(Self {
             send: Mutex::new(Some(send)),
             recv,
-        }
+            on_full,
+            dropped: AtomicUsize::new(0),
+        })
     }
     pub fn close(&self) {
         *self.send.lock().unwrap() = None;
     }
     pub fn put(&self, value: PyObject, py: Python<'_>) -> PyResult<()> {
-        match self.send.lock().unwrap().as_ref() {
+        // The sender is cloned out of the lock so that a put blocking on a full queue doesn't prevent closing it.
+        let send = self.send.lock().unwrap().clone();
+        match send.as_ref() {
             None => Err(pyo3::exceptions::PyBrokenPipeError::new_err(
                 "Attempted to put on closed Queue",
             )),
-            Some(send) => Python::allow_threads(py, || {
-                send.send(value).unwrap();
-                Ok(())
-            }),
-        }
-    }
-    pub fn get(&self, timeout: Option<f32>, py: Python<'_>) -> PyResult<PyObject> {
-        Python::allow_threads(py, || match timeout {
-            None => match self.recv.recv() {
-                Ok(value) => Ok(value),
-                Err(_) => Err(pyo3::exceptions::PyStopIteration::new_err(())),
-            },
-            Some(secs) => match self
-                .recv
-                .recv_timeout(std::time::Duration::from_secs_f32(secs))
-            {
-                Ok(value) => Ok(value),
-                Err(flume::RecvTimeoutError::Timeout) => {
-                    Err(pyo3::exceptions::PyTimeoutError::new_err(()))
+            Some(send) => match self.on_full {
+                OnFull::Block => Python::allow_threads(py, || {
+                    send.send(value).unwrap();
+                    Ok(())
+                }),
+                OnFull::DropLatest => {
+                    if let Err(flume::TrySendError::Full(_)) = send.try_send(value) {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Ok(())
                 }
-                Err(flume::RecvTimeoutError::Disconnected) => {
-                    Err(pyo3::exceptions::PyStopIteration::new_err(()))
+                OnFull::DropOldest => {
+                    let mut value = value;
+                    while let Err(flume::TrySendError::Full(v)) = send.try_send(value) {
+                        if self.recv.try_recv().is_ok() {
+                            self.dropped.fetch_add(1, Ordering::Relaxed);
+                        }
+                        value = v;
+                    }
+                    Ok(())
                 }
             },
-        })
+        }
     }
-    pub fn get_remaining(&self, timeout: Option<f32>, py: Python<'_>) -> PyResult<Py<PyList>> {
-        Python::allow_threads(py, || {
-            let vec = match timeout {
-                None => self.recv.iter().collect::<Vec<_>>(),
-                Some(secs) => {
-                    let deadline =
-                        std::time::Instant::now() + std::time::Duration::from_secs_f32(secs);
-                    let mut vec = Vec::new();
-                    loop {
-                        match self.recv.recv_deadline(deadline) {
-                            Ok(v) => vec.push(v),
-                            Err(flume::RecvTimeoutError::Disconnected) => break,
-                            Err(flume::RecvTimeoutError::Timeout) => {
-                                let list: Py<PyList> =
-                                    Python::with_gil(|py| PyList::new(py, vec).into_py(py));
-                                return Err(pyo3::exceptions::PyTimeoutError::new_err((list,)));
-                            }
-                        }
-                    }
-                    vec
+    pub fn dropped_count(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+    pub fn get(&self, timeout: Option<f64>, py: Python<'_>) -> PyResult<PyObject> {
+        let deadline = deadline("timeout", timeout)?;
+        match self.recv_until(deadline, py)? {
+            Ok(value) => Ok(value),
+            Err(flume::RecvTimeoutError::Timeout) => {
+                Err(pyo3::exceptions::PyTimeoutError::new_err(()))
+            }
+            Err(flume::RecvTimeoutError::Disconnected) => {
+                Err(pyo3::exceptions::PyStopIteration::new_err(()))
+            }
+        }
+    }
+    pub fn try_recv(&self) -> Option<PyObject> {
+        self.recv.try_recv().ok()
+    }
+    pub fn recv(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.get(None, py)
+    }
+    pub fn recv_timeout(&self, timeout: f64, py: Python<'_>) -> PyResult<PyObject> {
+        self.get(Some(timeout), py)
+    }
+    pub fn get_remaining(&self, timeout: Option<f64>, py: Python<'_>) -> PyResult<Py<PyList>> {
+        let deadline = deadline("timeout", timeout)?;
+        let mut vec = Vec::new();
+        loop {
+            match self.recv_until(deadline, py)? {
+                Ok(v) => vec.push(v),
+                Err(flume::RecvTimeoutError::Disconnected) => break,
+                Err(flume::RecvTimeoutError::Timeout) => {
+                    let list: Py<PyList> = PyList::new(py, vec).into_py(py);
+                    return Err(pyo3::exceptions::PyTimeoutError::new_err((list,)));
                 }
+            }
+        }
+        Ok(PyList::new(py, vec).into_py(py))
+    }
+    pub fn recv_batch(
+        &self,
+        max_size: Option<usize>,
+        max_latency: Option<f64>,
+        timeout: Option<f64>,
+        py: Python<'_>,
+    ) -> PyResult<Py<PyList>> {
+        let max_size = match max_size {
+            Some(0) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "`max_size` must be at least 1",
+                ))
+            }
+            max_size => max_size.unwrap_or(usize::MAX),
+        };
+        let max_latency = max_latency
+            .map(|secs| crate::duration("max_latency", secs))
+            .transpose()?;
+        let mut vec = vec![self.get(timeout, py)?];
+        // The latency is counted from the arrival of the batch's first value.
+        let deadline = max_latency.map(|latency| std::time::Instant::now() + latency);
+        while vec.len() < max_size {
+            let value = match deadline {
+                Some(deadline) => self.recv_until(Some(deadline), py)?.ok(),
+                None => self.recv.try_recv().ok(),
             };
-            Ok(Python::with_gil(|py| PyList::new(py, vec).into_py(py)))
-        })
+            match value {
+                Some(value) => vec.push(value),
+                None => break,
+            }
+        }
+        Ok(PyList::new(py, vec).into_py(py))
+    }
+    pub fn drain(&self, py: Python<'_>) -> Py<PyList> {
+        let vec = self.recv.try_iter().collect::<Vec<_>>();
+        PyList::new(py, vec).into_py(py)
     }
     pub fn is_closed(&self) -> bool {
         self.send.lock().unwrap().is_none()
     }
 }
+
+/// Converts the optional `timeout` (in seconds) passed as the `name` argument into a deadline.
+fn deadline(name: &str, timeout: Option<f64>) -> PyResult<Option<std::time::Instant>> {
+    timeout
+        .map(|secs| Ok(std::time::Instant::now() + crate::duration(name, secs)?))
+        .transpose()
+}
+
+/// How long a blocking receive may run without the GIL before checking for pending signals.
+const SIGNAL_CHECK_PERIOD: std::time::Duration = std::time::Duration::from_millis(100);
+
+impl _Queue {
+    /// Waits for a value until `deadline` (or forever if `None`), releasing the GIL while waiting.
+    ///
+    /// The wait is split into short slices, between which pending signals are checked, so that a
+    /// `KeyboardInterrupt` raised by Ctrl-C propagates promptly instead of once a value arrives.
+    fn recv_until(
+        &self,
+        deadline: Option<std::time::Instant>,
+        py: Python<'_>,
+    ) -> PyResult<Result<PyObject, flume::RecvTimeoutError>> {
+        loop {
+            let slice = std::time::Instant::now() + SIGNAL_CHECK_PERIOD;
+            let slice = match deadline {
+                Some(deadline) if deadline < slice => deadline,
+                _ => slice,
+            };
+            match py.allow_threads(|| self.recv.recv_deadline(slice)) {
+                Err(flume::RecvTimeoutError::Timeout)
+                    if deadline.map_or(true, |deadline| slice < deadline) =>
+                {
+                    py.check_signals()?
+                }
+                result => return Ok(result),
+            }
+        }
+    }
+}
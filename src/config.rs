@@ -17,6 +17,7 @@
 use pyo3::prelude::*;
 use validated_struct::ValidatedMap;
 use zenoh::config::{Config, Notifier};
+use zenoh::prelude::ZenohId;
 use zenoh_core::zerror;
 
 use crate::{ToPyErr, ToPyResult};
@@ -33,36 +34,53 @@ impl Default for PyConfig {
 }
 impl PyConfig {
     pub fn take(&mut self) -> Option<Config> {
-        if let Self::Config(c) = self {
-            let c = unsafe { std::ptr::read(c) };
-            unsafe { std::ptr::write(self, PyConfig::None) }
-            Some(*c)
-        } else {
-            None
+        match std::mem::replace(self, PyConfig::None) {
+            PyConfig::Config(c) => Some(*c),
+            other => {
+                *self = other;
+                None
+            }
         }
     }
 }
 #[pyclass(subclass)]
-pub struct _Config(pub(crate) PyConfig);
+pub struct _Config(
+    pub(crate) PyConfig,
+    /// The fields that were explicitly set, by the source the configuration was read from or by `insert_json5`,
+    /// as a JSON object: these are the fields `merge` applies, whether or not they hold their default value.
+    serde_json::Value,
+);
+impl From<PyConfig> for _Config {
+    fn from(config: PyConfig) -> Self {
+        _Config(config, serde_json::Value::Object(Default::default()))
+    }
+}
 
 #[pymethods]
 impl _Config {
     #[allow(clippy::new_without_default)]
     #[new]
     pub fn new() -> Self {
-        _Config(Default::default())
+        _Config::from(PyConfig::default())
     }
     #[staticmethod]
     pub fn from_file(expr: &str) -> PyResult<Self> {
-        match Config::from_file(expr) {
-            Ok(k) => Ok(Self(PyConfig::Config(Box::new(k)))),
-            Err(e) => Err(e.to_pyerr()),
-        }
+        let config = Config::from_file(expr).map_err(|e| e.to_pyerr())?;
+        let source = std::fs::read_to_string(expr).to_pyres()?;
+        let explicit = if expr.ends_with(".yaml") || expr.ends_with(".yml") {
+            serde_yaml::from_str(&source).to_pyres()?
+        } else {
+            json5::from_str(&source).to_pyres()?
+        };
+        Ok(Self(PyConfig::Config(Box::new(config)), explicit))
     }
     #[staticmethod]
     pub fn from_json5(expr: &str) -> PyResult<Self> {
         match Config::from_deserializer(&mut json5::Deserializer::from_str(expr).to_pyres()?) {
-            Ok(k) => Ok(Self(PyConfig::Config(Box::new(k)))),
+            Ok(k) => Ok(Self(
+                PyConfig::Config(Box::new(k)),
+                json5::from_str(expr).to_pyres()?,
+            )),
             Err(Ok(_)) => Err(zenoh_core::zerror!(
                 "{} did parse into a config, but invalid values were found",
                 expr,
@@ -72,6 +90,24 @@ impl _Config {
         }
     }
 
+    #[staticmethod]
+    pub fn peer() -> PyResult<Self> {
+        Self::with_mode("peer")
+    }
+    #[staticmethod]
+    pub fn client(endpoints: Vec<String>) -> PyResult<Self> {
+        let mut config = Self::with_mode("client")?;
+        config.insert_json5(
+            "connect/endpoints",
+            &serde_json::to_string(&endpoints).to_pyres()?,
+        )?;
+        Ok(config)
+    }
+    #[staticmethod]
+    pub fn router() -> PyResult<Self> {
+        Self::with_mode("router")
+    }
+
     pub fn get_json(&self, path: &str) -> PyResult<String> {
         match &self.0 {
             PyConfig::None => Err(zerror!("Attempted to use a destroyed configuration").to_pyerr()),
@@ -80,7 +116,198 @@ impl _Config {
         }
     }
 
+    pub fn set_id(&mut self, id: &str) -> PyResult<()> {
+        let zid: ZenohId = match id.parse() {
+            Ok(zid) => zid,
+            Err(e) => {
+                return Err(zerror!("`{}` isn't a valid ZenohId: {}", id, e).to_pyerr());
+            }
+        };
+        self.insert_json5("id", &serde_json::to_string(&zid.to_string()).to_pyres()?)
+    }
+
     pub fn insert_json5(&mut self, path: &str, value: &str) -> PyResult<()> {
+        self.insert(path, value)?;
+        let mut explicit = &mut self.1;
+        for chunk in path.split('/').filter(|chunk| !chunk.is_empty()) {
+            if !explicit.is_object() {
+                *explicit = serde_json::Value::Object(Default::default());
+            }
+            explicit = explicit
+                .as_object_mut()
+                .unwrap()
+                .entry(chunk)
+                .or_insert(serde_json::Value::Null);
+        }
+        *explicit = json5::from_str(value).to_pyres()?;
+        Ok(())
+    }
+
+    #[pyo3(name = "to_json")]
+    pub fn pyto_json(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(&self.to_json()?).to_pyres()
+    }
+
+    pub fn __str__(&self) -> PyResult<String> {
+        self.pyto_json()
+    }
+
+    pub fn diff(&self, other: &Self) -> PyResult<Vec<String>> {
+        let mut paths = Vec::new();
+        diff_json(
+            &self.to_json()?,
+            &other.to_json()?,
+            &mut Vec::new(),
+            &mut paths,
+        );
+        Ok(paths
+            .iter()
+            .map(|path| {
+                path.iter()
+                    .map(|chunk| format!("/{}", chunk.replace('~', "~0").replace('/', "~1")))
+                    .collect()
+            })
+            .collect())
+    }
+
+    pub fn merge(&mut self, other: &Self) -> PyResult<()> {
+        let mut merged = self.to_json()?;
+        merge_json(&mut merged, &other.1);
+        // Sections are inserted whole, so that keys holding a `/` (which insertion paths can't address) are merged too.
+        if let serde_json::Value::Object(sections) = &other.1 {
+            for key in sections.keys() {
+                let section = merged.get(key).unwrap_or(&serde_json::Value::Null);
+                self.insert(key, &section.to_string())?;
+            }
+        }
+        merge_json(&mut self.1, &other.1);
+        Ok(())
+    }
+}
+
+/// Merges `b` into `a`: objects are merged key by key, while anything else, arrays included, replaces what it's merged into.
+fn merge_json(a: &mut serde_json::Value, b: &serde_json::Value) {
+    match (a, b) {
+        (serde_json::Value::Object(a), serde_json::Value::Object(b)) => {
+            for (key, value) in b {
+                merge_json(a.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (a, b) => *a = b.clone(),
+    }
+}
+
+/// Pushes the path of every leaf that differs between `a` and `b` onto `paths`.
+///
+/// Objects are compared key by key, while anything else, arrays included, is compared as a whole.
+fn diff_json(
+    a: &serde_json::Value,
+    b: &serde_json::Value,
+    path: &mut Vec<String>,
+    paths: &mut Vec<Vec<String>>,
+) {
+    match (a, b) {
+        (serde_json::Value::Object(a), serde_json::Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            let null = serde_json::Value::Null;
+            for key in keys {
+                path.push(key.clone());
+                diff_json(
+                    a.get(key).unwrap_or(&null),
+                    b.get(key).unwrap_or(&null),
+                    path,
+                    paths,
+                );
+                path.pop();
+            }
+        }
+        (a, b) if a != b => paths.push(path.clone()),
+        _ => {}
+    }
+}
+/// The protocols whose endpoints are addressed as `<host>:<port>`.
+const SOCKET_PROTOCOLS: [&str; 5] = ["tcp", "udp", "tls", "quic", "ws"];
+
+/// Checks `config` for the most common misconfigurations, before any networking starts.
+///
+/// Endpoints are only checked syntactically: whether their protocol is supported is left to Zenoh, which depends on the
+/// transports it was built with.
+///
+/// All the problems found are reported at once, in a single error.
+pub(crate) fn validate(config: &Config) -> PyResult<()> {
+    let read = |path: &str| -> serde_json::Value {
+        config
+            .get_json(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    };
+    let endpoints = |path: &str| -> Vec<String> {
+        match read(path) {
+            serde_json::Value::Array(endpoints) => endpoints
+                .into_iter()
+                .filter_map(|e| e.as_str().map(str::to_owned))
+                .collect(),
+            _ => Vec::new(),
+        }
+    };
+    let mut problems = Vec::new();
+    let connect = endpoints("connect/endpoints");
+    let listen = endpoints("listen/endpoints");
+    for (path, endpoints) in [
+        ("connect/endpoints", &connect),
+        ("listen/endpoints", &listen),
+    ] {
+        for endpoint in endpoints {
+            let locator = endpoint.split(['?', '#']).next().unwrap_or_default();
+            let Some((protocol, address)) = locator
+                .split_once('/')
+                .filter(|(protocol, address)| !protocol.is_empty() && !address.is_empty())
+            else {
+                problems.push(format!("`{}`: `{}` must be of the form `<protocol>/<address>`", path, endpoint));
+                continue;
+            };
+            if SOCKET_PROTOCOLS.contains(&protocol) {
+                let port = address
+                    .rsplit_once(':')
+                    .map(|(_, port)| port.parse::<u16>());
+                if !matches!(port, Some(Ok(_))) {
+                    problems.push(format!(
+                        "`{}`: `{}` must be of the form `{}/<host>:<port>`",
+                        path, endpoint, protocol
+                    ));
+                }
+            }
+        }
+    }
+    let client = read("mode").as_str() == Some("client");
+    let multicast = read("scouting/multicast/enabled").as_bool();
+    if client && connect.is_empty() && multicast == Some(false) {
+        problems.push("`mode` is `client`, but no `connect/endpoints` are set and multicast scouting is disabled: routers can't be found".to_string());
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(zerror!("Invalid configuration:\n- {}", problems.join("\n- ")).to_pyerr())
+    }
+}
+
+impl _Config {
+    fn to_json(&self) -> PyResult<serde_json::Value> {
+        match &self.0 {
+            PyConfig::None => Err(zerror!("Attempted to use a destroyed configuration").to_pyerr()),
+            PyConfig::Config(c) => serde_json::to_value(&**c).to_pyres(),
+            PyConfig::Notifier(c) => serde_json::to_value(&*c.lock()).to_pyres(),
+        }
+    }
+    fn with_mode(mode: &str) -> PyResult<Self> {
+        let mut config = Self::new();
+        config.insert_json5("mode", &serde_json::to_string(mode).to_pyres()?)?;
+        Ok(config)
+    }
+    fn insert(&mut self, path: &str, value: &str) -> PyResult<()> {
         match &mut self.0 {
             PyConfig::None => Err(zerror!("Attempted to use a destroyed configuration").to_pyerr()),
             PyConfig::Config(c) => c.insert_json5(path, value).to_pyres(),
@@ -13,11 +13,20 @@
 //
 use crate::ToPyErr;
 use pyo3::prelude::*;
+use zenoh::config::whatami::{WhatAmI, WhatAmIMatcher};
 use zenoh::prelude::{Encoding, KnownEncoding, Priority, SampleKind};
 use zenoh::publication::CongestionControl;
 use zenoh::query::{ConsolidationMode, QueryTarget};
 use zenoh::subscriber::Reliability;
 
+/// Whether `a` and `b` are the same encoding, suffix included.
+///
+/// Encodings are compared by their known prefix and their suffix rather than structurally, since an encoding
+/// with an empty suffix is the same as the bare known encoding.
+pub(crate) fn encodings_match(a: &Encoding, b: &Encoding) -> bool {
+    a.prefix() == b.prefix() && a.suffix() == b.suffix()
+}
+
 #[pyclass(subclass)]
 #[repr(transparent)]
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -82,21 +91,111 @@ impl _Encoding {
     pub fn from_str(s: String) -> Self {
         Self(s.into())
     }
+    #[staticmethod]
+    pub fn known() -> Vec<Self> {
+        vec![
+            Self::EMPTY,
+            Self::APP_OCTET_STREAM,
+            Self::APP_CUSTOM,
+            Self::TEXT_PLAIN,
+            Self::APP_PROPERTIES,
+            Self::APP_JSON,
+            Self::APP_SQL,
+            Self::APP_INTEGER,
+            Self::APP_FLOAT,
+            Self::APP_XML,
+            Self::APP_XHTML_XML,
+            Self::APP_X_WWW_FORM_URLENCODED,
+            Self::TEXT_JSON,
+            Self::TEXT_HTML,
+            Self::TEXT_XML,
+            Self::TEXT_CSS,
+            Self::TEXT_CSV,
+            Self::TEXT_JAVASCRIPT,
+            Self::IMAGE_JPEG,
+            Self::IMAGE_PNG,
+            Self::IMAGE_GIF,
+        ]
+    }
     pub fn __str__(&self) -> String {
         self.0.to_string()
     }
+    #[getter]
+    pub fn suffix(&self) -> &str {
+        self.0.suffix()
+    }
     pub fn append(&mut self, suffix: String) {
-        unsafe {
-            let mut tmp = std::ptr::read(&self.0);
-            tmp = tmp.with_suffix(suffix);
-            std::ptr::write(&mut self.0, tmp);
-        }
+        let encoding = std::mem::replace(&mut self.0, Encoding::Exact(KnownEncoding::Empty));
+        self.0 = encoding.with_suffix(suffix);
     }
     pub fn equals(&self, other: &Self) -> bool {
         self == other
     }
 }
 
+/// Whether `s` is a MIME token, as defined by RFC 2045.
+fn is_mime_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`{|}~".contains(c))
+}
+
+/// Parses a MIME type with optional parameters, such as `Application/JSON; Schema=v2`, into a normalized encoding.
+///
+/// The type, subtype and parameter names are lowercased and surrounding whitespace is dropped, while parameter values
+/// are kept as is. Types of the known encoding table become that known encoding, with the parameters as their suffix.
+#[pyfunction]
+pub fn parse_encoding(mime: &str) -> PyResult<_Encoding> {
+    let invalid =
+        |problem: &str| zenoh_core::zerror!("Invalid MIME type `{}`: {}", mime, problem).to_pyerr();
+    let mut parts = mime.split(';');
+    let essence = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+    if essence.is_empty() {
+        return match parts.next() {
+            None => Ok(_Encoding::EMPTY),
+            Some(_) => Err(invalid("parameters must follow a type")),
+        };
+    }
+    match essence.split_once('/') {
+        Some((kind, subtype)) if is_mime_token(kind) && is_mime_token(subtype) => {}
+        Some(_) => {
+            return Err(invalid(
+                "the type and subtype must be non-empty MIME tokens",
+            ))
+        }
+        None => return Err(invalid("expected a `<type>/<subtype>` pair")),
+    }
+    let mut suffix = String::new();
+    for parameter in parts {
+        match parameter.split_once('=') {
+            Some((name, value)) if is_mime_token(name.trim()) && !value.trim().is_empty() => {
+                suffix.push(';');
+                suffix.push_str(&name.trim().to_ascii_lowercase());
+                suffix.push('=');
+                suffix.push_str(value.trim());
+            }
+            _ => {
+                return Err(invalid(&format!(
+                    "expected a `<name>=<value>` parameter, found `{}`",
+                    parameter.trim()
+                )))
+            }
+        }
+    }
+    let encoding = match _Encoding::known()
+        .into_iter()
+        .find(|known| known.0.to_string() == essence)
+    {
+        Some(known) => known.0,
+        None => Encoding::Exact(KnownEncoding::Empty).with_suffix(essence),
+    };
+    Ok(_Encoding(if suffix.is_empty() {
+        encoding
+    } else {
+        encoding.with_suffix(suffix)
+    }))
+}
+
 #[pyclass(subclass)]
 #[derive(Clone, PartialEq, Eq)]
 pub struct _Priority(pub(crate) Priority);
@@ -161,6 +260,18 @@ impl _SampleKind {
     pub const PUT: Self = Self(SampleKind::Put);
     #[classattr]
     pub const DELETE: Self = Self(SampleKind::Delete);
+    #[staticmethod]
+    pub fn from_str(s: &str) -> PyResult<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "PUT" => Ok(Self::PUT),
+            "DELETE" => Ok(Self::DELETE),
+            _ => Err(zenoh_core::zerror!(
+                "`{}` isn't a sample kind: expected `PUT` or `DELETE`",
+                s
+            )
+            .to_pyerr()),
+        }
+    }
     pub fn __str__(&self) -> &'static str {
         match self.0 {
             SampleKind::Put => "PUT",
@@ -174,6 +285,12 @@ impl _SampleKind {
             _ => Err(zenoh_core::zerror!("SampleKind does not support comparison").to_pyerr()),
         }
     }
+    fn __hash__(&self) -> u64 {
+        match self.0 {
+            SampleKind::Put => 0,
+            SampleKind::Delete => 1,
+        }
+    }
 }
 
 #[pyclass(subclass)]
@@ -294,3 +411,85 @@ impl _QueryConsolidation {
     #[classattr]
     pub const LATEST: Self = Self(Some(ConsolidationMode::Latest));
 }
+
+#[pyclass(subclass)]
+#[derive(Clone, PartialEq, Eq)]
+pub struct _WhatAmI(pub(crate) WhatAmI);
+#[pymethods]
+impl _WhatAmI {
+    #[new]
+    pub fn new(this: Self) -> Self {
+        this
+    }
+    fn __richcmp__(&self, other: &Self, op: pyo3::pyclass::CompareOp) -> PyResult<bool> {
+        match op {
+            pyo3::pyclass::CompareOp::Eq => Ok(self == other),
+            pyo3::pyclass::CompareOp::Ne => Ok(self != other),
+            _ => Err(zenoh_core::zerror!("WhatAmI does not support comparison").to_pyerr()),
+        }
+    }
+    fn __hash__(&self) -> u64 {
+        match self.0 {
+            WhatAmI::Router => 0,
+            WhatAmI::Peer => 1,
+            WhatAmI::Client => 2,
+        }
+    }
+    #[classattr]
+    pub const ROUTER: Self = Self(WhatAmI::Router);
+    #[classattr]
+    pub const PEER: Self = Self(WhatAmI::Peer);
+    #[classattr]
+    pub const CLIENT: Self = Self(WhatAmI::Client);
+    pub fn __str__(&self) -> &'static str {
+        match self.0 {
+            WhatAmI::Router => "router",
+            WhatAmI::Peer => "peer",
+            WhatAmI::Client => "client",
+        }
+    }
+}
+
+#[pyclass(subclass)]
+#[derive(Clone)]
+pub struct _WhatAmIMatcher(pub(crate) WhatAmIMatcher);
+#[pymethods]
+impl _WhatAmIMatcher {
+    #[new]
+    pub fn new(this: Self) -> Self {
+        this
+    }
+    #[staticmethod]
+    pub fn from_str(s: &str) -> PyResult<Self> {
+        match s.parse() {
+            Ok(w) => Ok(Self(w)),
+            Err(_) => Err(zenoh_core::zerror!("Couldn't parse `{}` into a WhatAmIMatcher: must be a `|`-separated list of `peer`, `client` or `router`", s).to_pyerr()),
+        }
+    }
+    #[staticmethod]
+    pub fn from_list(whatamis: Vec<_WhatAmI>) -> PyResult<Self> {
+        if whatamis.is_empty() {
+            return Err(zenoh_core::zerror!(
+                "A WhatAmIMatcher must match at least one of `peer`, `client` or `router`"
+            )
+            .to_pyerr());
+        }
+        let s = whatamis
+            .iter()
+            .map(|w| w.__str__())
+            .collect::<Vec<_>>()
+            .join("|");
+        Self::from_str(&s)
+    }
+    pub fn matches(&self, whatami: &_WhatAmI) -> bool {
+        self.0.matches(whatami.0)
+    }
+    pub fn __str__(&self) -> String {
+        [_WhatAmI::ROUTER, _WhatAmI::PEER, _WhatAmI::CLIENT]
+            .iter()
+            .filter(|w| self.matches(w))
+            .map(|w| w.__str__())
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+}
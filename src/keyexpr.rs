@@ -12,18 +12,79 @@
 //   ZettaScale Zenoh team, <zenoh@zettascale.tech>
 //
 
-use pyo3::prelude::*;
+use pyo3::{prelude::*, types::PyDict};
 use std::{
     collections::{hash_map::DefaultHasher, HashMap},
     convert::{TryFrom, TryInto},
 };
-use zenoh::prelude::{sync::SyncResolve, KeyExpr, Selector};
+use zenoh::prelude::{KeyExpr, Selector};
 
 use crate::{session::_Session, ToPyErr};
 
 #[pyclass(subclass)]
 #[derive(Clone)]
 pub struct _KeyExpr(pub(crate) KeyExpr<'static>);
+impl From<KeyExpr<'static>> for _KeyExpr {
+    fn from(key_expr: KeyExpr<'static>) -> Self {
+        _KeyExpr(key_expr)
+    }
+}
+
+fn invalid(expr: &str, problem: &str) -> PyErr {
+    zenoh_core::zerror!("Invalid key expression `{}`: {}", expr, problem).to_pyerr()
+}
+
+/// Explains why `expr` isn't a valid key expression, pointing at the offending character (counted in chars).
+///
+/// Returns `None` if no such problem was found, leaving canonization issues to Zenoh's own validation.
+fn diagnose(expr: &str) -> Option<String> {
+    if expr.is_empty() {
+        return Some("key expressions can't be empty".to_string());
+    }
+    let chars: Vec<char> = expr.chars().collect();
+    let mut chunk_start = 0;
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            '#' => return Some(format!("`#` at position {} is reserved", i)),
+            '?' => {
+                return Some(format!(
+                    "`?` at position {} starts a selector's parameters, which key expressions can't have: use a `Selector` instead",
+                    i
+                ))
+            }
+            '$' if chars.get(i + 1) != Some(&'*') => {
+                return Some(format!(
+                    "`$` at position {} may only be used as part of the `$*` wildcard",
+                    i
+                ))
+            }
+            '/' if i == chunk_start => return Some(empty_chunk(i)),
+            '/' => chunk_start = i + 1,
+            _ => {}
+        }
+    }
+    if chunk_start == chars.len() {
+        return Some(empty_chunk(chunk_start));
+    }
+    let mut position = 0;
+    for chunk in expr.split('/') {
+        if chunk != "*" && chunk != "**" && chunk.replace("$*", "").contains('*') {
+            return Some(format!(
+                "chunk `{}` at position {} mixes `*` with other characters: `*` and `**` must be whole chunks, use `$*` within a chunk",
+                chunk, position
+            ));
+        }
+        position += chunk.chars().count() + 1;
+    }
+    None
+}
+
+fn empty_chunk(position: usize) -> String {
+    format!(
+        "empty chunk at position {}: key expressions can't have leading, trailing or consecutive `/`",
+        position
+    )
+}
 
 #[pymethods]
 impl _KeyExpr {
@@ -33,18 +94,36 @@ impl _KeyExpr {
     }
     #[staticmethod]
     pub fn new(expr: String) -> PyResult<Self> {
-        match expr.try_into() {
-            Ok(k) => Ok(Self(k)),
-            Err(e) => Err(e.to_pyerr()),
+        match diagnose(&expr) {
+            Some(problem) => Err(invalid(&expr, &problem)),
+            None => match expr.try_into() {
+                Ok(k) => Ok(Self::from(k)),
+                Err(e) => Err(e.to_pyerr()),
+            },
         }
     }
     #[staticmethod]
     pub fn autocanonize(expr: String) -> PyResult<Self> {
         match KeyExpr::autocanonize(expr) {
-            Ok(k) => Ok(Self(k)),
+            Ok(k) => Ok(Self::from(k)),
             Err(e) => Err(e.to_pyerr()),
         }
     }
+    #[staticmethod]
+    pub fn try_from(expr: String, py: Python<'_>) -> PyObject {
+        match Self::new(expr) {
+            Ok(k) => k.into_py(py),
+            Err(e) => e.into_py(py),
+        }
+    }
+    #[staticmethod]
+    pub fn has_wildcards(expr: &str) -> bool {
+        expr.contains('*')
+    }
+
+    pub fn is_wild(&self) -> bool {
+        self.0.is_wild()
+    }
 
     pub fn intersects(&self, other: &Self) -> bool {
         self.0.intersects(&other.0)
@@ -58,12 +137,20 @@ impl _KeyExpr {
         self.0 == other.0
     }
 
+    /// Key expressions have no escape sequences: `/` is always a chunk separator, and the canonized string has no
+    /// empty chunks, so splitting it on `/` gives exactly the chunks Zenoh matches, wildcards included.
+    pub fn chunks(&self) -> Vec<&str> {
+        self.0.as_str().split('/').collect()
+    }
+
+    pub fn strip_prefix(&self, prefix: &Self) -> Option<Self> {
+        let remainder = self.0.as_str().strip_prefix(prefix.0.as_str())?;
+        let remainder = remainder.strip_prefix('/')?;
+        KeyExpr::try_from(remainder.to_owned()).ok().map(Self::from)
+    }
+
     pub fn undeclare(&self, session: &_Session) -> PyResult<()> {
-        session
-            .0
-            .undeclare(self.0.clone())
-            .res_sync()
-            .map_err(|e| e.to_pyerr())
+        session.undeclare_keyexpr(self)
     }
 
     pub fn __str__(&self) -> &str {
@@ -82,6 +169,19 @@ impl _KeyExpr {
     }
 }
 
+/// A key expression argument, which may be passed either as a `_KeyExpr` or as a `str`.
+///
+/// Strings are validated on extraction, raising a `ZError` at the call if they aren't valid key expressions.
+pub(crate) struct IntoKeyExpr(pub(crate) KeyExpr<'static>);
+impl<'a> FromPyObject<'a> for IntoKeyExpr {
+    fn extract(ob: &'a PyAny) -> PyResult<Self> {
+        match ob.extract::<_KeyExpr>() {
+            Ok(k) => Ok(Self(k.0)),
+            Err(_) => Ok(Self(_KeyExpr::new(ob.extract()?)?.0)),
+        }
+    }
+}
+
 #[pyclass(subclass)]
 #[derive(Clone)]
 pub struct _Selector(pub(crate) Selector<'static>);
@@ -100,7 +200,7 @@ impl _Selector {
     }
     #[getter]
     pub fn key_expr(&self) -> _KeyExpr {
-        _KeyExpr(self.0.key_expr.clone())
+        _KeyExpr::from(self.0.key_expr.clone())
     }
     #[getter]
     pub fn get_parameters(&self) -> &str {
@@ -110,6 +210,15 @@ impl _Selector {
     pub fn set_parameters(&mut self, parameters: String) {
         self.0.set_parameters(parameters)
     }
+    pub fn with_parameters(&self, parameters: &PyDict) -> PyResult<Self> {
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+        for (k, v) in parameters.iter() {
+            serializer.append_pair(k.extract()?, v.extract()?);
+        }
+        let mut selector = self.0.clone();
+        selector.set_parameters(serializer.finish());
+        Ok(Self(selector))
+    }
     pub fn decode_parameters(&self) -> PyResult<HashMap<String, String>> {
         self.0.parameters_map().map_err(|e| e.to_pyerr())
     }
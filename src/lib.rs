@@ -39,6 +39,16 @@ impl<T, E: ToPyErr> ToPyResult<T> for Result<T, E> {
     }
 }
 
+/// Converts `secs`, the value of the `name` argument, into a duration, raising a `ValueError` if it is negative or not finite.
+pub(crate) fn duration(name: &str, secs: f64) -> PyResult<std::time::Duration> {
+    std::time::Duration::try_from_secs_f64(secs).map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "`{}` must be a finite, non-negative number of seconds, got {}",
+            name, secs
+        ))
+    })
+}
+
 enum ExtractError {
     Unavailable(Option<PyErr>),
     Other(PyErr),
@@ -69,12 +79,14 @@ impl<K: ToPyObject> PyExtract<K> for PyDict {
 }
 
 #[pymodule]
-fn zenoh(_py: Python, m: &PyModule) -> PyResult<()> {
+fn zenoh(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("ZError", py.get_type::<ZError>())?;
     m.add_class::<config::_Config>()?;
     m.add_class::<closures::_Queue>()?;
     m.add_class::<keyexpr::_KeyExpr>()?;
     m.add_class::<keyexpr::_Selector>()?;
     m.add_class::<session::_Session>()?;
+    m.add_class::<session::_QueryHandle>()?;
     m.add_class::<session::_Publisher>()?;
     m.add_class::<session::_Subscriber>()?;
     m.add_class::<session::_PullSubscriber>()?;
@@ -82,6 +94,7 @@ fn zenoh(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<queryable::_Query>()?;
     m.add_class::<queryable::_Queryable>()?;
     m.add_class::<value::_Value>()?;
+    m.add_class::<value::_PayloadSlice>()?;
     m.add_class::<value::_Sample>()?;
     m.add_class::<value::_Reply>()?;
     m.add_class::<value::_Timestamp>()?;
@@ -94,8 +107,11 @@ fn zenoh(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<enums::_Reliability>()?;
     m.add_class::<enums::_QueryConsolidation>()?;
     m.add_class::<enums::_QueryTarget>()?;
+    m.add_class::<enums::_WhatAmI>()?;
+    m.add_class::<enums::_WhatAmIMatcher>()?;
     m.add_wrapped(wrap_pyfunction!(init_logger))?;
     m.add_wrapped(wrap_pyfunction!(session::scout))?;
+    m.add_wrapped(wrap_pyfunction!(enums::parse_encoding))?;
     Ok(())
 }
 
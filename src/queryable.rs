@@ -11,21 +11,34 @@
 // Contributors:
 //   ZettaScale Zenoh team, <zenoh@zettascale.tech>
 //
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
 
 use pyo3::prelude::*;
 use zenoh::{
+    prelude::{Sample, Value},
     queryable::{Query, Queryable},
     selector::Parameters,
 };
 use zenoh_core::SyncResolve;
 
 use crate::{
-    keyexpr::{_KeyExpr, _Selector},
+    enums::{_Encoding, _SampleKind},
+    keyexpr::{IntoKeyExpr, _KeyExpr, _Selector},
     value::{_Sample, _Value},
     ToPyErr,
 };
 
+/// The query parameter through which `get` tells queryables which reply encodings it accepts.
+///
+/// Its value is a comma-separated list of encodings, in order of preference.
+pub(crate) const ACCEPT_KEY: &str = "_accept";
+
 #[pyclass(subclass)]
 #[derive(Clone)]
 pub struct _Query(pub(crate) Arc<Query>);
@@ -37,7 +50,7 @@ impl _Query {
     }
     #[getter]
     pub fn key_expr(&self) -> _KeyExpr {
-        _KeyExpr(self.0.key_expr().clone())
+        _KeyExpr::from(self.0.key_expr().clone())
     }
     #[getter]
     pub fn parameters(&self) -> &str {
@@ -64,6 +77,21 @@ impl _Query {
         Ok(res)
     }
     #[getter]
+    pub fn accept(&self) -> Vec<_Encoding> {
+        self.0
+            .parameters()
+            .decode()
+            .filter(|(k, _)| k == ACCEPT_KEY)
+            .flat_map(|(_, v)| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|e| !e.is_empty())
+                    .map(|e| _Encoding::from_str(e.to_owned()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+    #[getter]
     pub fn selector(&self) -> _Selector {
         _Selector(self.0.selector().clone().into_owned())
     }
@@ -77,6 +105,33 @@ impl _Query {
             .res_sync()
             .map_err(|e| e.to_pyerr())
     }
+    pub fn reply_sample(&self, sample: _Sample) -> PyResult<()> {
+        self.reply(sample)
+    }
+    pub fn reply_all(
+        &self,
+        items: Vec<(IntoKeyExpr, _Value)>,
+        kind: Option<_SampleKind>,
+    ) -> PyResult<usize> {
+        let count = items.len();
+        for (sent, (key_expr, value)) in items.into_iter().enumerate() {
+            let mut sample = Sample::new(key_expr.0, Value::from(value));
+            if let Some(kind) = &kind {
+                sample.kind = kind.0;
+            }
+            if let Err(e) = self.0.reply(Ok(sample)).res_sync() {
+                return Err(zenoh_core::zerror!(
+                    "Reply {} of {} failed, after {} replies were sent: {}",
+                    sent + 1,
+                    count,
+                    sent,
+                    e
+                )
+                .to_pyerr());
+            }
+        }
+        Ok(count)
+    }
 }
 impl From<Query> for _Query {
     fn from(q: Query) -> Self {
@@ -85,4 +140,21 @@ impl From<Query> for _Query {
 }
 
 #[pyclass(subclass)]
-pub struct _Queryable(pub(crate) Queryable<'static, ()>);
+pub struct _Queryable(
+    pub(crate) Mutex<Option<Queryable<'static, ()>>>,
+    pub(crate) Arc<AtomicU64>,
+    pub(crate) bool,
+);
+#[pymethods]
+impl _Queryable {
+    #[getter]
+    pub fn complete(&self) -> bool {
+        self.2
+    }
+    pub fn stats(&self) -> HashMap<&'static str, u64> {
+        HashMap::from([("queries_received", self.1.load(Ordering::Relaxed))])
+    }
+    pub fn undeclare(&self, py: Python<'_>) {
+        crate::session::undeclare(py, &self.0)
+    }
+}
@@ -14,112 +14,265 @@
 
 #![allow(clippy::borrow_deref_ref)] // false positives with pyo3 macros
 
+use std::collections::HashMap;
 use std::convert::TryInto;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
 
-use pyo3::{prelude::*, types::PyDict};
+use pyo3::{
+    prelude::*,
+    types::{PyBytes, PyDict},
+};
+use validated_struct::ValidatedMap;
 use zenoh::config::whatami::{WhatAmI, WhatAmIMatcher};
-use zenoh::prelude::SessionDeclarations;
+use zenoh::prelude::{
+    CongestionControl, KeyExpr, Priority, Sample, SampleKind, SessionDeclarations,
+};
 use zenoh::publication::Publisher;
+use zenoh::query::Reply;
 use zenoh::scouting::Scout;
 use zenoh::subscriber::{PullSubscriber, Subscriber};
 use zenoh::Session;
+use zenoh_buffers::SplitBuffer;
 use zenoh_core::SyncResolve;
 
 use crate::closures::PyClosure;
 use crate::config::{PyConfig, _Config};
 use crate::enums::{
-    _CongestionControl, _Priority, _QueryConsolidation, _QueryTarget, _Reliability, _SampleKind,
+    _CongestionControl, _Encoding, _Priority, _QueryConsolidation, _QueryTarget, _Reliability,
+    _SampleKind, _WhatAmIMatcher, encodings_match,
 };
-use crate::keyexpr::{_KeyExpr, _Selector};
-use crate::queryable::{_Query, _Queryable};
-use crate::value::{_Hello, _Reply, _Sample, _Value, _ZenohId};
+use crate::keyexpr::{IntoKeyExpr, _KeyExpr, _Selector};
+use crate::queryable::{_Query, _Queryable, ACCEPT_KEY};
+use crate::value::{Payload, _Hello, _Reply, _Sample, _Value, _ZenohId};
 use crate::{PyAnyToValue, PyExtract, ToPyErr};
 
+#[derive(Clone, Default)]
+struct SessionStats {
+    puts_sent: Arc<AtomicU64>,
+    deletes_sent: Arc<AtomicU64>,
+    gets_sent: Arc<AtomicU64>,
+    replies_received: Arc<AtomicU64>,
+    keyexprs_declared: Arc<AtomicU64>,
+}
+
 #[pyclass(subclass)]
 #[derive(Clone)]
-pub struct _Session(pub(crate) Arc<Session>);
+pub struct _Session {
+    pub(crate) session: Arc<Session>,
+    zid: _ZenohId,
+    stats: SessionStats,
+    qos: Arc<Mutex<DefaultQos>>,
+}
+
+/// The QoS applied by the operations that aren't given one explicitly.
+#[derive(Clone, Copy, Default)]
+struct DefaultQos {
+    priority: Option<Priority>,
+    congestion_control: Option<CongestionControl>,
+}
 
 #[pymethods]
 impl _Session {
     #[new]
-    pub fn new(mut config: Option<&mut crate::config::_Config>) -> PyResult<Self> {
-        let c = match &mut config {
-            Some(c) => c.0.take().unwrap_or_default(),
-            None => Default::default(),
+    pub fn new(
+        mut config: Option<&mut crate::config::_Config>,
+        validate: Option<bool>,
+        attempts: Option<u32>,
+        delay: Option<f64>,
+        py: Python<'_>,
+    ) -> PyResult<Self> {
+        let taken = config.as_mut().and_then(|c| c.0.take());
+        let restore = taken.is_some();
+        let c = taken.unwrap_or_default();
+        let checked = match validate.unwrap_or(true) {
+            true => crate::config::validate(&c),
+            false => Ok(()),
+        };
+        let opened = checked.and_then(|_| {
+            let attempts = attempts.unwrap_or(1).max(1);
+            let delay = crate::duration("delay", delay.unwrap_or(1.0))?;
+            let mut attempt = 1;
+            loop {
+                match py.allow_threads(|| zenoh::open(c.clone()).res_sync()) {
+                    Ok(session) => break Ok(session),
+                    Err(e) if attempt >= attempts => {
+                        break Err(zenoh_core::zerror!(
+                            "Failed to open session after {} attempt(s): {}",
+                            attempts,
+                            e
+                        )
+                        .to_pyerr())
+                    }
+                    Err(e) => log::warn!("Attempt {} to open session failed: {}", attempt, e),
+                }
+                py.allow_threads(|| std::thread::sleep(delay));
+                py.check_signals()?;
+                attempt += 1;
+            }
+        });
+        let session = match opened {
+            Ok(session) => session,
+            Err(e) => {
+                // Give the configuration back, so that it may be fixed and reused.
+                if let (true, Some(config)) = (restore, config) {
+                    config.0 = PyConfig::Config(Box::new(c));
+                }
+                return Err(e);
+            }
         };
-        let session = zenoh::open(c).res_sync().map_err(|e| e.to_pyerr())?;
         if let Some(config) = config {
-            *config = _Config(PyConfig::Notifier(session.config().clone()))
+            config.0 = PyConfig::Notifier(session.config().clone())
         }
-        Ok(_Session(Arc::new(session)))
+        Ok(_Session {
+            zid: _ZenohId(session.zid()),
+            session: Arc::new(session),
+            stats: SessionStats::default(),
+            qos: Default::default(),
+        })
     }
     pub fn config(&self) -> _Config {
-        _Config(PyConfig::Notifier(self.0.config().clone()))
+        _Config::from(PyConfig::Notifier(self.session.config().clone()))
+    }
+
+    pub fn set_default_qos(
+        &self,
+        priority: Option<_Priority>,
+        congestion_control: Option<_CongestionControl>,
+    ) {
+        let mut qos = self.qos.lock().unwrap();
+        if let Some(priority) = priority {
+            qos.priority = Some(priority.0);
+        }
+        if let Some(congestion_control) = congestion_control {
+            qos.congestion_control = Some(congestion_control.0);
+        }
+    }
+    pub fn reset_default_qos(&self) {
+        *self.qos.lock().unwrap() = DefaultQos::default();
     }
 
     #[pyo3(signature = (key_expr, value, **kwargs))]
     pub fn put(
         &self,
-        key_expr: &crate::keyexpr::_KeyExpr,
+        key_expr: IntoKeyExpr,
         value: &PyAny,
         kwargs: Option<&PyDict>,
-    ) -> PyResult<()> {
-        let s = &self.0;
+    ) -> PyResult<usize> {
+        let s = &self.session;
         let k = &key_expr.0;
         let v = value.to_value()?;
-        let mut builder = s.put(k, v);
+        let size = v.payload.len();
+        let (priority, congestion_control) = self.resolve_qos(kwargs)?;
+        let mut builder = s
+            .put(k, v)
+            .priority(priority)
+            .congestion_control(congestion_control);
+        let mut validate_only = false;
         if let Some(kwargs) = kwargs {
-            match kwargs.extract_item::<_SampleKind>("kind") {
-                Ok(kind) => builder = builder.kind(kind.0),
+            match kwargs.extract_item::<bool>("validate_only") {
+                Ok(value) => validate_only = value,
                 Err(crate::ExtractError::Other(e)) => return Err(e),
                 _ => {}
             }
-            match kwargs.extract_item::<_CongestionControl>("congestion_control") {
-                Ok(congestion_control) => {
-                    builder = builder.congestion_control(congestion_control.0)
+            match kwargs.extract_item::<_SampleKind>("kind") {
+                Ok(kind) if kind.0 == SampleKind::Delete => {
+                    return Err(zenoh_core::zerror!(
+                        "`put` can't be used with `kind=SampleKind.DELETE()` on `{}`: use `delete` instead",
+                        k
+                    )
+                    .to_pyerr())
                 }
+                Ok(kind) => builder = builder.kind(kind.0),
                 Err(crate::ExtractError::Other(e)) => return Err(e),
                 _ => {}
             }
-            match kwargs.extract_item::<_Priority>("priority") {
-                Ok(priority) => builder = builder.priority(priority.0),
-                Err(crate::ExtractError::Other(e)) => return Err(e),
-                _ => {}
-            }
         }
-        builder.res_sync().map_err(|e| e.to_pyerr())
+        if validate_only {
+            return Ok(size);
+        }
+        builder.res_sync().map_err(|e| e.to_pyerr())?;
+        self.stats.puts_sent.fetch_add(1, Ordering::Relaxed);
+        Ok(size)
     }
 
-    #[pyo3(signature = (key_expr, **kwargs))]
-    pub fn delete(
+    #[pyo3(signature = (key_expr, values, **kwargs))]
+    pub fn put_stream(
         &self,
-        key_expr: &crate::keyexpr::_KeyExpr,
+        key_expr: IntoKeyExpr,
+        values: &PyAny,
         kwargs: Option<&PyDict>,
-    ) -> PyResult<()> {
-        let s = &self.0;
+        py: Python<'_>,
+    ) -> PyResult<u64> {
+        let (priority, congestion_control) = self.resolve_qos(kwargs)?;
         let k = &key_expr.0;
-        let mut builder = s.delete(k);
+        let mut published = 0;
+        for value in values.iter()? {
+            let result = value.and_then(|value| value.to_value()).and_then(|v| {
+                py.allow_threads(|| {
+                    self.session
+                        .put(k, v)
+                        .priority(priority)
+                        .congestion_control(congestion_control)
+                        .res_sync()
+                        .map_err(|e| e.to_pyerr())
+                })
+            });
+            if let Err(e) = result {
+                return Err(zenoh_core::zerror!(
+                    "`put_stream` on `{}` stopped after {} values were published: {}",
+                    k,
+                    published,
+                    e
+                )
+                .to_pyerr());
+            }
+            published += 1;
+            self.stats.puts_sent.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(published)
+    }
+
+    #[pyo3(signature = (key_expr, **kwargs))]
+    pub fn delete(&self, key_expr: IntoKeyExpr, kwargs: Option<&PyDict>) -> PyResult<()> {
+        let s = &self.session;
+        let k = &key_expr.0;
+        let allow_wildcard =
+            match kwargs.map(|kwargs| kwargs.extract_item::<bool>("allow_wildcard")) {
+                Some(Ok(allow_wildcard)) => allow_wildcard,
+                Some(Err(crate::ExtractError::Other(e))) => return Err(e),
+                _ => false,
+            };
+        if !allow_wildcard && k.is_wild() {
+            return Err(zenoh_core::zerror!(
+                "Refusing to delete `{}` as it contains wildcards: pass `allow_wildcard=True` if you really mean to delete every matching key",
+                k
+            )
+            .to_pyerr());
+        }
+        let (priority, congestion_control) = self.resolve_qos(kwargs)?;
+        let mut builder = s
+            .delete(k)
+            .priority(priority)
+            .congestion_control(congestion_control);
         if let Some(kwargs) = kwargs {
             match kwargs.extract_item::<_SampleKind>("kind") {
-                Ok(kind) => builder = builder.kind(kind.0),
-                Err(crate::ExtractError::Other(e)) => return Err(e),
-                _ => {}
-            }
-            match kwargs.extract_item::<_CongestionControl>("congestion_control") {
-                Ok(congestion_control) => {
-                    builder = builder.congestion_control(congestion_control.0)
+                Ok(kind) if kind.0 == SampleKind::Put => {
+                    return Err(zenoh_core::zerror!(
+                        "`delete` can't be used with `kind=SampleKind.PUT()` on `{}`: use `put` instead",
+                        k
+                    )
+                    .to_pyerr())
                 }
-                Err(crate::ExtractError::Other(e)) => return Err(e),
-                _ => {}
-            }
-            match kwargs.extract_item::<_Priority>("priority") {
-                Ok(priority) => builder = builder.priority(priority.0),
+                Ok(kind) => builder = builder.kind(kind.0),
                 Err(crate::ExtractError::Other(e)) => return Err(e),
                 _ => {}
             }
         }
-        builder.res_sync().map_err(|e| e.to_pyerr())
+        builder.res_sync().map_err(|e| e.to_pyerr())?;
+        self.stats.deletes_sent.fetch_add(1, Ordering::Relaxed);
+        Ok(())
     }
 
     #[pyo3(signature = (selector, callback, **kwargs))]
@@ -128,9 +281,68 @@ impl _Session {
         selector: &_Selector,
         callback: &PyAny,
         kwargs: Option<&PyDict>,
-    ) -> PyResult<()> {
+    ) -> PyResult<_QueryHandle> {
         let callback: PyClosure<(_Reply,)> = <_ as TryInto<_>>::try_into(callback)?;
-        let mut builder = self.0.get(&selector.0).with(callback);
+        let callback = callback.counted(self.stats.replies_received.clone());
+        let callback = Arc::new(Mutex::new(Some(Arc::new(callback))));
+        // Zenoh's callback must be the only strong owner of the closure, so that finalizing the query drops it.
+        let timeout = match kwargs.map(|kwargs| kwargs.extract_item::<f64>("timeout")) {
+            Some(Ok(timeout)) => crate::duration("timeout", timeout)?,
+            Some(Err(crate::ExtractError::Other(e))) => return Err(e),
+            _ => self.default_query_timeout(),
+        };
+        let state = Arc::new(QueryState {
+            finalized: AtomicBool::new(false),
+            timed_out: AtomicBool::new(false),
+            deadline: Instant::now() + timeout,
+        });
+        let handle = _QueryHandle {
+            callback: Arc::downgrade(&callback),
+            cancelled: AtomicBool::new(false),
+            state: state.clone(),
+        };
+        let finalizer = Finalizer(state);
+        let encoding = match kwargs.map(|kwargs| kwargs.extract_item::<_Encoding>("encoding")) {
+            Some(Ok(encoding)) => Some(encoding.0),
+            Some(Err(crate::ExtractError::Other(e))) => return Err(e),
+            _ => None,
+        };
+        let mut selector = selector.0.clone();
+        match kwargs.map(|kwargs| kwargs.extract_item::<Vec<_Encoding>>("accept")) {
+            Some(Ok(accept)) if !accept.is_empty() => {
+                let accept = accept
+                    .iter()
+                    .map(|e| e.0.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let mut parameters = selector.parameters().to_owned();
+                if !parameters.is_empty() {
+                    parameters.push('&');
+                }
+                parameters.push_str(
+                    &form_urlencoded::Serializer::new(String::new())
+                        .append_pair(ACCEPT_KEY, &accept)
+                        .finish(),
+                );
+                selector.set_parameters(parameters);
+            }
+            Some(Err(crate::ExtractError::Other(e))) => return Err(e),
+            _ => {}
+        }
+        let mut builder = self.session.get(selector).callback(move |reply: Reply| {
+            // Zenoh's callback owns the finalizer, which is only dropped along with it.
+            let _ = &finalizer;
+            if let (Some(encoding), Ok(sample)) = (&encoding, &reply.sample) {
+                if !encodings_match(&sample.value.encoding, encoding) {
+                    return;
+                }
+            }
+            let callback = callback.lock().unwrap().clone();
+            if let Some(callback) = callback {
+                callback.invoke(reply)
+            }
+        });
+        builder = builder.timeout(timeout);
         if let Some(kwargs) = kwargs {
             match kwargs.extract_item::<_QueryConsolidation>("consolidation") {
                 Ok(_QueryConsolidation(Some(value))) => builder = builder.consolidation(value),
@@ -148,34 +360,59 @@ impl _Session {
                 _ => {}
             }
         }
-        builder.res_sync().map_err(|e| e.to_pyerr())
+        builder.res_sync().map_err(|e| e.to_pyerr())?;
+        self.stats.gets_sent.fetch_add(1, Ordering::Relaxed);
+        Ok(handle)
     }
 
-    pub fn declare_keyexpr(&self, key_expr: &_KeyExpr) -> PyResult<_KeyExpr> {
-        match self.0.declare_keyexpr(&key_expr.0).res_sync() {
-            Ok(k) => Ok(_KeyExpr(k.into_owned())),
+    pub fn declare_keyexpr(&self, key_expr: IntoKeyExpr) -> PyResult<_KeyExpr> {
+        match self.session.declare_keyexpr(&key_expr.0).res_sync() {
+            Ok(k) => {
+                self.stats.keyexprs_declared.fetch_add(1, Ordering::Relaxed);
+                Ok(_KeyExpr::from(k.into_owned()))
+            }
             Err(e) => Err(e.to_pyerr()),
         }
     }
 
+    pub fn undeclare_keyexpr(&self, key_expr: &_KeyExpr) -> PyResult<()> {
+        self.session
+            .undeclare(key_expr.0.clone())
+            .res_sync()
+            .map_err(|e| e.to_pyerr())?;
+        // Key expressions that weren't declared through this session mustn't wrap the counter around.
+        let _ = self.stats.keyexprs_declared.fetch_update(
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            |declared| declared.checked_sub(1),
+        );
+        Ok(())
+    }
+
     #[pyo3(signature = (key_expr, callback, **kwargs))]
     pub fn declare_queryable(
         &self,
-        key_expr: _KeyExpr,
+        key_expr: IntoKeyExpr,
         callback: &PyAny,
         kwargs: Option<&PyDict>,
     ) -> PyResult<_Queryable> {
         let callback: PyClosure<(_Query,)> = <_ as TryInto<_>>::try_into(callback)?;
-        let mut builder = self.0.declare_queryable(key_expr.0).with(callback);
+        let queries_received = Arc::new(AtomicU64::new(0));
+        let callback = callback.counted(queries_received.clone());
+        let mut builder = self.session.declare_queryable(key_expr.0).with(callback);
+        let mut complete = false;
         if let Some(kwargs) = kwargs {
             match kwargs.extract_item::<bool>("complete") {
-                Ok(value) => builder = builder.complete(value),
+                Ok(value) => {
+                    complete = value;
+                    builder = builder.complete(value)
+                }
                 Err(crate::ExtractError::Other(e)) => return Err(e),
                 _ => {}
             }
         }
         match builder.res_sync() {
-            Ok(o) => Ok(_Queryable(o)),
+            Ok(o) => Ok(_Queryable(Mutex::new(Some(o)), queries_received, complete)),
             Err(e) => Err(e.to_pyerr()),
         }
     }
@@ -183,24 +420,32 @@ impl _Session {
     #[pyo3(signature = (key_expr, **kwargs))]
     pub fn declare_publisher(
         &self,
-        key_expr: _KeyExpr,
+        key_expr: IntoKeyExpr,
         kwargs: Option<&PyDict>,
     ) -> PyResult<_Publisher> {
-        let mut builder = self.0.declare_publisher(key_expr.0);
+        let (priority, congestion_control) = self.resolve_qos(kwargs)?;
+        let mut encoding = None;
         if let Some(kwargs) = kwargs {
-            match kwargs.extract_item::<_Priority>("priority") {
-                Ok(value) => builder = builder.priority(value.0),
-                Err(crate::ExtractError::Other(e)) => return Err(e),
-                _ => {}
-            }
-            match kwargs.extract_item::<_CongestionControl>("congestion_control") {
-                Ok(value) => builder = builder.congestion_control(value.0),
+            match kwargs.extract_item::<_Encoding>("encoding") {
+                Ok(value) => encoding = Some(value),
                 Err(crate::ExtractError::Other(e)) => return Err(e),
                 _ => {}
             }
         }
+        let builder = self
+            .session
+            .declare_publisher(key_expr.0)
+            .priority(priority)
+            .congestion_control(congestion_control);
         match builder.res_sync() {
-            Ok(o) => Ok(_Publisher(o)),
+            Ok(publisher) => Ok(_Publisher {
+                key_expr: publisher.key_expr().clone(),
+                publisher: Arc::new(Mutex::new(Some(Arc::new(publisher)))),
+                stats: Default::default(),
+                encoding,
+                priority,
+                congestion_control,
+            }),
             Err(e) => Err(e.to_pyerr()),
         }
     }
@@ -208,12 +453,47 @@ impl _Session {
     #[pyo3(signature = (key_expr, callback, **kwargs))]
     pub fn declare_subscriber(
         &self,
-        key_expr: &_KeyExpr,
+        key_expr: IntoKeyExpr,
         callback: &PyAny,
         kwargs: Option<&PyDict>,
     ) -> PyResult<_Subscriber> {
-        let callback: PyClosure<(_Sample,)> = <_ as TryInto<_>>::try_into(callback)?;
-        let mut builder = self.0.declare_subscriber(&key_expr.0).with(callback);
+        let extract = match kwargs.map(|kwargs| kwargs.extract_item::<String>("extract")) {
+            Some(Ok(extract)) => Some(extract),
+            Some(Err(crate::ExtractError::Other(e))) => return Err(e),
+            _ => None,
+        };
+        let samples_received = Arc::new(AtomicU64::new(0));
+        // Only the parts of the sample the callback asked for are converted into Python objects.
+        let handler: Box<dyn Fn(Sample) + Send + Sync> = match extract.as_deref() {
+            None | Some("sample") => {
+                let callback: PyClosure<(_Sample,)> = <_ as TryInto<_>>::try_into(callback)?;
+                let callback = callback.counted(samples_received.clone());
+                Box::new(move |sample| callback.invoke(sample))
+            }
+            Some("value") => {
+                let callback: PyClosure<(_Value,)> = <_ as TryInto<_>>::try_into(callback)?;
+                let callback = callback.counted(samples_received.clone());
+                Box::new(move |sample| callback.invoke(sample.value))
+            }
+            Some("bytes") => {
+                let callback: PyClosure<(Py<PyBytes>,)> = <_ as TryInto<_>>::try_into(callback)?;
+                let callback = callback.counted(samples_received.clone());
+                Box::new(move |sample| {
+                    callback.invoke(Payload::from(sample.value.payload).into_pybytes())
+                })
+            }
+            Some(extract) => {
+                return Err(zenoh_core::zerror!(
+                    "`extract` must be one of \"sample\", \"value\" or \"bytes\", got `{}`",
+                    extract
+                )
+                .to_pyerr())
+            }
+        };
+        let mut builder = self
+            .session
+            .declare_subscriber(&key_expr.0)
+            .callback(move |sample| handler(sample));
         if let Some(kwargs) = kwargs {
             match kwargs.extract_item::<_Reliability>("reliability") {
                 Ok(reliabilty) => builder = builder.reliability(reliabilty.0),
@@ -222,19 +502,25 @@ impl _Session {
             }
         }
         let subscriber = builder.res().map_err(|e| e.to_pyerr())?;
-        Ok(_Subscriber(subscriber))
+        Ok(_Subscriber {
+            subscriber: Mutex::new(Some(subscriber)),
+            key_expr: key_expr.0,
+            samples_received,
+        })
     }
 
     #[pyo3(signature = (key_expr, callback, **kwargs))]
     pub fn declare_pull_subscriber(
         &self,
-        key_expr: &_KeyExpr,
+        key_expr: IntoKeyExpr,
         callback: &PyAny,
         kwargs: Option<&PyDict>,
     ) -> PyResult<_PullSubscriber> {
         let callback: PyClosure<(_Sample,)> = <_ as TryInto<_>>::try_into(callback)?;
+        let samples_received = Arc::new(AtomicU64::new(0));
+        let callback = callback.counted(samples_received.clone());
         let mut builder = self
-            .0
+            .session
             .declare_subscriber(&key_expr.0)
             .pull_mode()
             .with(callback);
@@ -246,14 +532,18 @@ impl _Session {
             }
         }
         let subscriber = builder.res().map_err(|e| e.to_pyerr())?;
-        Ok(_PullSubscriber(subscriber))
+        Ok(_PullSubscriber {
+            subscriber: Mutex::new(Some(subscriber)),
+            key_expr: key_expr.0,
+            samples_received,
+        })
     }
 
     pub fn zid(&self) -> _ZenohId {
-        _ZenohId(self.0.zid())
+        self.zid
     }
     pub fn routers_zid(&self) -> Vec<_ZenohId> {
-        self.0
+        self.session
             .info()
             .routers_zid()
             .res_sync()
@@ -261,13 +551,152 @@ impl _Session {
             .collect()
     }
     pub fn peers_zid(&self) -> Vec<_ZenohId> {
-        self.0.info().peers_zid().res_sync().map(_ZenohId).collect()
+        self.session
+            .info()
+            .peers_zid()
+            .res_sync()
+            .map(_ZenohId)
+            .collect()
+    }
+    pub fn stats(&self) -> HashMap<&'static str, u64> {
+        let stats = &self.stats;
+        HashMap::from([
+            ("puts_sent", stats.puts_sent.load(Ordering::Relaxed)),
+            ("deletes_sent", stats.deletes_sent.load(Ordering::Relaxed)),
+            ("gets_sent", stats.gets_sent.load(Ordering::Relaxed)),
+            (
+                "replies_received",
+                stats.replies_received.load(Ordering::Relaxed),
+            ),
+            (
+                "keyexprs_declared",
+                stats.keyexprs_declared.load(Ordering::Relaxed),
+            ),
+        ])
+    }
+}
+impl _Session {
+    /// The priority and congestion control passed in `kwargs`, each falling back to the session's default QoS.
+    fn resolve_qos(&self, kwargs: Option<&PyDict>) -> PyResult<(Priority, CongestionControl)> {
+        let qos = *self.qos.lock().unwrap();
+        let mut priority = qos.priority.unwrap_or_default();
+        let mut congestion_control = qos.congestion_control.unwrap_or_default();
+        if let Some(kwargs) = kwargs {
+            match kwargs.extract_item::<_Priority>("priority") {
+                Ok(value) => priority = value.0,
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+            match kwargs.extract_item::<_CongestionControl>("congestion_control") {
+                Ok(value) => congestion_control = value.0,
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+        }
+        Ok((priority, congestion_control))
+    }
+
+    /// The timeout Zenoh applies to the queries that aren't given one, as configured by `queries_default_timeout`.
+    fn default_query_timeout(&self) -> Duration {
+        let millis = self
+            .session
+            .config()
+            .lock()
+            .get_json("queries_default_timeout")
+            .ok()
+            .and_then(|json| serde_json::from_str::<u64>(&json).ok())
+            .unwrap_or(DEFAULT_QUERY_TIMEOUT_MS);
+        Duration::from_millis(millis)
+    }
+}
+/// Zenoh's default for `queries_default_timeout`, in milliseconds.
+const DEFAULT_QUERY_TIMEOUT_MS: u64 = 10_000;
+
+/// How a query was finalized, as observed by its callback.
+struct QueryState {
+    finalized: AtomicBool,
+    timed_out: AtomicBool,
+    /// When Zenoh finalizes the query by its timeout.
+    deadline: Instant,
+}
+
+/// Owned by a query's callback, whose dropping by Zenoh marks the query as finalized.
+///
+/// A query that is only finalized once its deadline passed was finalized by its timeout.
+struct Finalizer(Arc<QueryState>);
+impl Drop for Finalizer {
+    fn drop(&mut self) {
+        if Instant::now() >= self.0.deadline {
+            self.0.timed_out.store(true, Ordering::Relaxed);
+        }
+        self.0.finalized.store(true, Ordering::Relaxed);
     }
 }
 
+#[pyclass(subclass)]
+pub struct _QueryHandle {
+    callback: Weak<Mutex<Option<Arc<PyClosure<(_Reply,)>>>>>,
+    cancelled: AtomicBool,
+    state: Arc<QueryState>,
+}
+#[pymethods]
+impl _QueryHandle {
+    pub fn cancel(&self, py: Python<'_>) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        // Once the last reference to the closure is gone (after any reply being delivered concurrently),
+        // it runs the handler's `drop`, closing its receiver. A finalized query has already dropped it.
+        let callback = self
+            .callback
+            .upgrade()
+            .and_then(|callback| callback.lock().unwrap().take());
+        py.allow_threads(|| drop(callback))
+    }
+    #[getter]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+    #[getter]
+    pub fn is_finalized(&self) -> bool {
+        self.state.finalized.load(Ordering::Relaxed)
+    }
+    #[getter]
+    pub fn timed_out(&self) -> bool {
+        self.state.timed_out.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Clone, Default)]
+struct PublisherStats {
+    puts_sent: Arc<AtomicU64>,
+    deletes_sent: Arc<AtomicU64>,
+}
+
 #[pyclass(subclass)]
 #[derive(Clone)]
-pub struct _Publisher(Publisher<'static>);
+pub struct _Publisher {
+    /// Shared by all the clones of the publisher, so that undeclaring it through one of them invalidates them all.
+    publisher: Arc<Mutex<Option<Arc<Publisher<'static>>>>>,
+    key_expr: KeyExpr<'static>,
+    stats: PublisherStats,
+    encoding: Option<_Encoding>,
+    /// The QoS the publisher was declared with, which its puts and deletes use.
+    priority: Priority,
+    congestion_control: CongestionControl,
+}
+impl _Publisher {
+    /// The lock is only held while cloning the publisher, so that a put delivered to a local subscriber's callback
+    /// may itself use the publisher.
+    fn get(&self) -> PyResult<Arc<Publisher<'static>>> {
+        match &*self.publisher.lock().unwrap() {
+            Some(publisher) => Ok(publisher.clone()),
+            None => Err(zenoh_core::zerror!(
+                "`{}`'s publisher was undeclared, possibly through another handle to it",
+                self.key_expr
+            )
+            .to_pyerr()),
+        }
+    }
+}
 #[pymethods]
 impl _Publisher {
     #[new]
@@ -276,45 +705,176 @@ impl _Publisher {
     }
     #[getter]
     pub fn key_expr(&self) -> _KeyExpr {
-        _KeyExpr(self.0.key_expr().clone())
+        _KeyExpr::from(self.key_expr.clone())
+    }
+    #[getter]
+    pub fn priority(&self) -> _Priority {
+        _Priority(self.priority)
+    }
+    #[getter]
+    pub fn congestion_control(&self) -> _CongestionControl {
+        _CongestionControl(self.congestion_control)
+    }
+    #[getter]
+    pub fn encoding(&self) -> Option<_Encoding> {
+        self.encoding.clone()
     }
-    pub fn put(&self, value: _Value) -> PyResult<()> {
-        self.0.put(value).res_sync().map_err(|e| e.to_pyerr())
+    pub fn put(&self, value: &PyAny, py: Python<'_>) -> PyResult<usize> {
+        let value = value.to_value_with_default(self.encoding.as_ref().map(|e| &e.0))?;
+        let size = value.payload.len();
+        let publisher = self.get()?;
+        py.allow_threads(|| publisher.put(value).res_sync())
+            .map_err(|e| e.to_pyerr())?;
+        self.stats.puts_sent.fetch_add(1, Ordering::Relaxed);
+        Ok(size)
     }
     pub fn delete(&self) -> PyResult<()> {
-        self.0.delete().res_sync().map_err(|e| e.to_pyerr())
+        self.get()?.delete().res_sync().map_err(|e| e.to_pyerr())?;
+        self.stats.deletes_sent.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+    pub fn stats(&self) -> HashMap<&'static str, u64> {
+        let stats = &self.stats;
+        HashMap::from([
+            ("puts_sent", stats.puts_sent.load(Ordering::Relaxed)),
+            ("deletes_sent", stats.deletes_sent.load(Ordering::Relaxed)),
+        ])
+    }
+    pub fn is_valid(&self) -> bool {
+        self.publisher.lock().unwrap().is_some()
+    }
+    pub fn undeclare(&self, py: Python<'_>) {
+        undeclare(py, &self.publisher)
     }
 }
 
 #[pyclass(subclass)]
-pub struct _Subscriber(Subscriber<'static, ()>);
+pub struct _Subscriber {
+    subscriber: Mutex<Option<Subscriber<'static, ()>>>,
+    key_expr: KeyExpr<'static>,
+    samples_received: Arc<AtomicU64>,
+}
+#[pymethods]
+impl _Subscriber {
+    #[getter]
+    pub fn key_expr(&self) -> _KeyExpr {
+        _KeyExpr::from(self.key_expr.clone())
+    }
+    pub fn stats(&self) -> HashMap<&'static str, u64> {
+        let samples_received = self.samples_received.load(Ordering::Relaxed);
+        HashMap::from([("samples_received", samples_received)])
+    }
+    pub fn undeclare(&self, py: Python<'_>) {
+        undeclare(py, &self.subscriber)
+    }
+}
 
 #[pyclass(subclass)]
-pub struct _PullSubscriber(PullSubscriber<'static, ()>);
+pub struct _PullSubscriber {
+    subscriber: Mutex<Option<PullSubscriber<'static, ()>>>,
+    key_expr: KeyExpr<'static>,
+    samples_received: Arc<AtomicU64>,
+}
 #[pymethods]
 impl _PullSubscriber {
+    #[getter]
+    pub fn key_expr(&self) -> _KeyExpr {
+        _KeyExpr::from(self.key_expr.clone())
+    }
     fn pull(&self) -> PyResult<()> {
-        self.0.pull().res_sync().map_err(|e| e.to_pyerr())
+        match &*self.subscriber.lock().unwrap() {
+            Some(subscriber) => subscriber.pull().res_sync().map_err(|e| e.to_pyerr()),
+            None => {
+                Err(zenoh_core::zerror!("Attempted to pull on an undeclared subscriber").to_pyerr())
+            }
+        }
+    }
+    pub fn stats(&self) -> HashMap<&'static str, u64> {
+        let samples_received = self.samples_received.load(Ordering::Relaxed);
+        HashMap::from([("samples_received", samples_received)])
+    }
+    pub fn undeclare(&self, py: Python<'_>) {
+        undeclare(py, &self.subscriber)
     }
 }
 
 #[pyclass(subclass)]
-pub struct _Scout(Scout<()>);
+pub struct _Scout(Mutex<Option<Scout<()>>>);
+#[pymethods]
+impl _Scout {
+    pub fn stop(&self, py: Python<'_>) {
+        undeclare(py, &self.0)
+    }
+}
+
+/// Drops the entity held in `slot` without holding the GIL.
+///
+/// Undeclaring an entity may wait on locks held by Zenoh threads that are themselves waiting for the GIL
+/// to run a callback, so it must never happen while the GIL is held. The callback's own destructor
+/// reacquires the GIL if needed. Undeclaring an already undeclared entity does nothing.
+pub(crate) fn undeclare<T: Send>(py: Python<'_>, slot: &Mutex<Option<T>>) {
+    let entity = slot.lock().unwrap().take();
+    py.allow_threads(|| drop(entity))
+}
 
 #[pyfunction]
-pub fn scout(callback: &PyAny, config: Option<&_Config>, what: Option<&str>) -> PyResult<_Scout> {
+pub fn scout(
+    callback: &PyAny,
+    config: Option<&_Config>,
+    what: Option<&PyAny>,
+    multicast_address: Option<&str>,
+    interface: Option<&str>,
+) -> PyResult<_Scout> {
     let callback: PyClosure<(_Hello,)> = <_ as TryInto<_>>::try_into(callback)?;
     let what: WhatAmIMatcher = match what {
         None => WhatAmI::Client | WhatAmI::Peer | WhatAmI::Router,
-        Some(s) => match s.parse() {
-            Ok(w) => w,
-            Err(_) => return Err(zenoh_core::zerror!("Couldn't parse `{}` into a WhatAmiMatcher: must be a `|`-separated list of `peer`, `client` or `router`", s).to_pyerr())
+        Some(what) => match what.extract::<_WhatAmIMatcher>() {
+            Ok(matcher) => matcher.0,
+            Err(_) => _WhatAmIMatcher::from_str(what.extract()?)?.0,
         },
     };
-    let config = config.and_then(|c| c.0.clone().take()).unwrap_or_default();
+    let mut config: zenoh::config::Config =
+        config.and_then(|c| c.0.clone().take()).unwrap_or_default();
+    if let Some(address) = multicast_address {
+        match address.parse::<std::net::SocketAddr>() {
+            Ok(addr) if addr.ip().is_multicast() => {}
+            Ok(_) => {
+                return Err(zenoh_core::zerror!(
+                "`{}` isn't a multicast address: scouting requires one, such as `224.0.0.224:7446`",
+                address
+            )
+                .to_pyerr())
+            }
+            Err(e) => {
+                return Err(zenoh_core::zerror!(
+                    "`{}` must be of the form `<multicast ip>:<port>`: {}",
+                    address,
+                    e
+                )
+                .to_pyerr())
+            }
+        }
+        config
+            .insert_json5(
+                "scouting/multicast/address",
+                &serde_json::to_string(address).map_err(|e| e.to_pyerr())?,
+            )
+            .map_err(|e| e.to_pyerr())?;
+    }
+    if let Some(interface) = interface {
+        if interface.trim().is_empty() {
+            return Err(zenoh_core::zerror!("`interface` can't be empty").to_pyerr());
+        }
+        config
+            .insert_json5(
+                "scouting/multicast/interface",
+                &serde_json::to_string(interface).map_err(|e| e.to_pyerr())?,
+            )
+            .map_err(|e| e.to_pyerr())?;
+    }
     let scout = zenoh::scout(what, config).with(callback).res_sync();
     match scout {
-        Ok(scout) => Ok(_Scout(scout)),
+        Ok(scout) => Ok(_Scout(Mutex::new(Some(scout)))),
         Err(e) => Err(e.to_pyerr()),
     }
 }
@@ -10,17 +10,21 @@
 // Contributors:
 //   ZettaScale Zenoh team, <zenoh@zettascale.tech>
 
-use pyo3::{prelude::*, types::PyBytes};
+use pyo3::{
+    prelude::*,
+    types::{PyBytes, PyDict, PyString},
+};
+use std::hash::{Hash, Hasher};
 use uhlc::Timestamp;
 use zenoh::{
-    prelude::{Encoding, KeyExpr, Sample, Value, ZenohId},
+    prelude::{Encoding, KeyExpr, KnownEncoding, Sample, Value, ZenohId},
     query::Reply,
     scouting::Hello,
 };
 use zenoh_buffers::{SplitBuffer, ZBuf};
 
 use crate::{
-    enums::{_Encoding, _SampleKind},
+    enums::{_Encoding, _SampleKind, _WhatAmI, encodings_match},
     keyexpr::_KeyExpr,
     ToPyErr,
 };
@@ -59,6 +63,44 @@ impl Payload {
         }
     }
 }
+impl Payload {
+    pub(crate) fn with_bytes<T>(&self, f: impl FnOnce(&[u8]) -> T) -> T {
+        match self {
+            Payload::Zenoh(buf) => f(&buf.contiguous()),
+            Payload::Python(buf) => Python::with_gil(|py| f(buf.as_bytes(py))),
+        }
+    }
+    /// One `_PayloadSlice` per slice of the underlying `ZBuf`, or the Python buffer if the payload was already converted.
+    pub(crate) fn slices(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        match self {
+            Payload::Zenoh(buf) => buf
+                .slices()
+                .map(|slice| {
+                    let slice = _PayloadSlice {
+                        _owner: buf.clone(),
+                        address: slice.as_ptr() as usize,
+                        len: slice.len(),
+                    };
+                    Ok(Py::new(py, slice)?.into_py(py))
+                })
+                .collect(),
+            Payload::Python(buf) => Ok(vec![buf.clone_ref(py).into_py(py)]),
+        }
+    }
+    /// Passes the payload to `f` slice by slice, without making it contiguous.
+    pub(crate) fn for_each_slice(&self, mut f: impl FnMut(&[u8])) {
+        match self {
+            Payload::Zenoh(buf) => buf.slices().for_each(f),
+            Payload::Python(buf) => Python::with_gil(|py| f(buf.as_bytes(py))),
+        }
+    }
+    pub(crate) fn len(&self, py: Python<'_>) -> usize {
+        match self {
+            Payload::Zenoh(buf) => buf.len(),
+            Payload::Python(buf) => buf.as_bytes(py).len(),
+        }
+    }
+}
 impl From<ZBuf> for Payload {
     fn from(buf: ZBuf) -> Self {
         Payload::Zenoh(buf)
@@ -69,6 +111,49 @@ impl From<Py<PyBytes>> for Payload {
         Payload::Python(buf)
     }
 }
+/// A slice of a `ZBuf`, which keeps the memory at `address` alive for as long as it exists.
+///
+/// It exports that memory through the read-only buffer protocol, so that `memoryview`s on it reference it.
+/// Under the stable ABI (the `abi3` feature), which lacks the buffer protocol, it can only be copied through `bytes()`.
+#[pyclass]
+pub struct _PayloadSlice {
+    _owner: ZBuf,
+    address: usize,
+    len: usize,
+}
+impl _PayloadSlice {
+    fn as_slice(&self) -> &[u8] {
+        // SAFETY: the memory belongs to `_owner`, which is never mutated and lives as long as `self`.
+        unsafe { std::slice::from_raw_parts(self.address as *const u8, self.len) }
+    }
+}
+#[pymethods]
+impl _PayloadSlice {
+    fn __len__(&self) -> usize {
+        self.len
+    }
+    fn __bytes__<'p>(&self, py: Python<'p>) -> &'p PyBytes {
+        PyBytes::new(py, self.as_slice())
+    }
+    #[cfg(not(feature = "abi3"))]
+    unsafe fn __getbuffer__(
+        slf: &PyCell<Self>,
+        view: *mut pyo3::ffi::Py_buffer,
+        flags: std::os::raw::c_int,
+    ) -> PyResult<()> {
+        // `PyBuffer_FillInfo` refuses writable requests, and makes `view` hold a reference to `slf`.
+        let this = slf.borrow();
+        let buf = this.address as *mut std::os::raw::c_void;
+        let len = this.len as pyo3::ffi::Py_ssize_t;
+        if pyo3::ffi::PyBuffer_FillInfo(view, slf.as_ptr(), buf, len, 1, flags) == -1 {
+            return Err(PyErr::fetch(slf.py()));
+        }
+        Ok(())
+    }
+    #[cfg(not(feature = "abi3"))]
+    unsafe fn __releasebuffer__(&self, _view: *mut pyo3::ffi::Py_buffer) {}
+}
+
 #[pyclass(subclass)]
 #[derive(Clone)]
 pub struct _Value {
@@ -88,16 +173,79 @@ impl _Value {
             encoding: encoding.map(|e| e.0).unwrap_or(Encoding::EMPTY),
         }
     }
+    #[staticmethod]
+    pub fn from_str(s: &str, encoding: Option<_Encoding>) -> Self {
+        Self {
+            payload: ZBuf::from(s.as_bytes().to_owned()).into(),
+            encoding: encoding
+                .map(|e| e.0)
+                .unwrap_or(Encoding::Exact(KnownEncoding::TextPlain)),
+        }
+    }
+    #[staticmethod]
+    pub fn from_bytes(payload: Py<PyBytes>, encoding: Option<_Encoding>) -> Self {
+        Self {
+            payload: payload.into(),
+            encoding: encoding
+                .map(|e| e.0)
+                .unwrap_or(Encoding::Exact(KnownEncoding::AppOctetStream)),
+        }
+    }
     #[getter]
     pub fn payload(&mut self) -> Py<PyBytes> {
         if let Payload::Python(buf) = &self.payload {
             return buf.clone();
         }
-        let payload = unsafe { std::ptr::read(&self.payload) };
+        let payload = std::mem::replace(&mut self.payload, Payload::Zenoh(ZBuf::default()));
         let buf = payload.into_pybytes();
-        unsafe { std::ptr::write(&mut self.payload, Payload::Python(buf.clone())) };
+        self.payload = Payload::Python(buf.clone());
         buf
     }
+    pub fn as_bytes(&mut self) -> Py<PyBytes> {
+        self.payload()
+    }
+    pub fn as_string(&self) -> PyResult<String> {
+        self.payload
+            .with_bytes(|bytes| std::str::from_utf8(bytes).map(|s| s.to_owned()))
+            .map_err(|e| e.to_pyerr())
+    }
+    pub fn payload_slices(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        self.payload.slices(py)
+    }
+    pub fn sha256(&self) -> String {
+        use sha2::Digest;
+        let mut hasher = sha2::Sha256::new();
+        self.payload.for_each_slice(|slice| hasher.update(slice));
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.payload.for_each_slice(|slice| hasher.write(slice));
+        hasher.finish()
+    }
+    fn __len__(&self, py: Python<'_>) -> usize {
+        self.payload.len(py)
+    }
+    fn __richcmp__(&self, other: &Self, op: pyo3::pyclass::CompareOp) -> PyResult<bool> {
+        match op {
+            pyo3::pyclass::CompareOp::Eq => Ok(self.same_as(other)),
+            pyo3::pyclass::CompareOp::Ne => Ok(!self.same_as(other)),
+            _ => Err(zenoh_core::zerror!("Value does not support comparison").to_pyerr()),
+        }
+    }
+    fn __hash__(&self) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.payload.for_each_slice(|slice| hasher.write(slice));
+        self.encoding.to_string().hash(&mut hasher);
+        hasher.finish()
+    }
+    pub fn is_empty(&self, py: Python<'_>) -> bool {
+        self.payload.len(py) == 0
+    }
     pub fn with_payload(&mut self, payload: Py<PyBytes>) {
         self.payload = Payload::Python(payload)
     }
@@ -108,6 +256,10 @@ impl _Value {
     pub fn with_encoding(&mut self, encoding: _Encoding) {
         self.encoding = encoding.0;
     }
+    #[getter]
+    pub fn encoding_suffix(&self) -> &str {
+        self.encoding.suffix()
+    }
 }
 impl From<Value> for _Value {
     fn from(value: Value) -> Self {
@@ -117,6 +269,20 @@ impl From<Value> for _Value {
         }
     }
 }
+impl _Value {
+    /// Compares the payloads byte for byte, regardless of how they are split into slices, and the encodings by their string forms.
+    fn same_as(&self, other: &Self) -> bool {
+        if self.encoding.to_string() != other.encoding.to_string() {
+            return false;
+        }
+        let bytes = |payload: &Payload| {
+            let mut bytes = Vec::new();
+            payload.for_each_slice(|slice| bytes.extend_from_slice(slice));
+            bytes
+        };
+        bytes(&self.payload) == bytes(&other.payload)
+    }
+}
 impl From<_Value> for Value {
     fn from(value: _Value) -> Self {
         Value::new(value.payload.into_zbuf()).encoding(value.encoding)
@@ -125,12 +291,42 @@ impl From<_Value> for Value {
 
 pub(crate) trait PyAnyToValue {
     fn to_value(self) -> PyResult<Value>;
+    /// Converts `self` into a value, using `default` as the encoding of raw `bytes`, `str` and buffers.
+    ///
+    /// Values that carry their own encoding keep it. Without `default`, `str` is encoded as `text/plain`,
+    /// and `bytes` or buffers as `application/octet-stream`.
+    fn to_value_with_default(self, default: Option<&Encoding>) -> PyResult<Value>;
 }
 impl PyAnyToValue for &PyAny {
     fn to_value(self) -> PyResult<Value> {
-        let encoding: _Encoding = self.getattr("encoding")?.extract()?;
-        let payload: &PyBytes = self.getattr("payload")?.extract()?;
-        Ok(Value::new(ZBuf::from(payload.as_bytes().to_owned())).encoding(encoding.0))
+        self.to_value_with_default(None)
+    }
+    fn to_value_with_default(self, default: Option<&Encoding>) -> PyResult<Value> {
+        if self.hasattr("encoding")? && self.hasattr("payload")? {
+            let encoding: _Encoding = self.getattr("encoding")?.extract()?;
+            let payload: &PyBytes = self.getattr("payload")?.extract()?;
+            return Ok(Value::new(ZBuf::from(payload.as_bytes().to_owned())).encoding(encoding.0));
+        }
+        let (payload, encoding) = if let Ok(s) = self.downcast::<PyString>() {
+            (s.to_str()?.as_bytes().to_owned(), KnownEncoding::TextPlain)
+        } else if let Ok(b) = self.downcast::<PyBytes>() {
+            (b.as_bytes().to_owned(), KnownEncoding::AppOctetStream)
+        } else {
+            let py = self.py();
+            let view = match py.import("builtins")?.getattr("memoryview")?.call1((self,)) {
+                Ok(view) => view,
+                Err(_) => {
+                    return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                        "Expected a Value, bytes, str or buffer, got {}",
+                        self.get_type().name()?
+                    )))
+                }
+            };
+            let bytes: &PyBytes = view.call_method0("tobytes")?.downcast()?;
+            (bytes.as_bytes().to_owned(), KnownEncoding::AppOctetStream)
+        };
+        let encoding = default.cloned().unwrap_or(Encoding::Exact(encoding));
+        Ok(Value::new(ZBuf::from(payload)).encoding(encoding))
     }
 }
 
@@ -141,6 +337,8 @@ pub struct _Sample {
     value: _Value,
     kind: _SampleKind,
     timestamp: Option<_Timestamp>,
+    source_id: Option<ZenohId>,
+    source_sn: Option<u64>,
 }
 impl From<Sample> for _Sample {
     fn from(sample: Sample) -> Self {
@@ -149,6 +347,7 @@ impl From<Sample> for _Sample {
             value,
             kind,
             timestamp,
+            source_info,
             ..
         } = sample;
         _Sample {
@@ -156,6 +355,8 @@ impl From<Sample> for _Sample {
             value: value.into(),
             kind: _SampleKind(kind),
             timestamp: timestamp.map(_Timestamp),
+            source_id: source_info.source_id,
+            source_sn: source_info.source_sn,
         }
     }
 }
@@ -219,17 +420,14 @@ impl _Sample {
     }
     #[getter]
     pub fn key_expr(&self) -> _KeyExpr {
-        _KeyExpr(self.key_expr.clone())
+        _KeyExpr::from(self.key_expr.clone())
     }
     #[getter]
     pub fn payload(&mut self) -> Py<PyBytes> {
-        if let Payload::Python(buf) = &self.value.payload {
-            return buf.clone();
-        }
-        let payload = unsafe { std::ptr::read(&self.value.payload) };
-        let buf = payload.into_pybytes();
-        unsafe { std::ptr::write(&mut self.value.payload, Payload::Python(buf.clone())) };
-        buf
+        self.value.payload()
+    }
+    pub fn payload_slices(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        self.value.payload_slices(py)
     }
     #[getter]
     pub fn encoding(&self) -> _Encoding {
@@ -243,6 +441,17 @@ impl _Sample {
     pub fn timestamp(&self) -> Option<_Timestamp> {
         self.timestamp
     }
+    pub fn matches_encoding(&self, encoding: &_Encoding) -> bool {
+        encodings_match(&self.value.encoding, &encoding.0)
+    }
+    #[getter]
+    pub fn source_id(&self) -> Option<_ZenohId> {
+        self.source_id.map(_ZenohId)
+    }
+    #[getter]
+    pub fn source_sn(&self) -> Option<u64> {
+        self.source_sn
+    }
     #[staticmethod]
     pub fn new(
         key_expr: _KeyExpr,
@@ -255,6 +464,8 @@ impl _Sample {
             value,
             kind,
             timestamp,
+            source_id: None,
+            source_sn: None,
         }
     }
 }
@@ -266,10 +477,14 @@ impl From<_Sample> for Sample {
             value,
             kind,
             timestamp,
+            source_id,
+            source_sn,
         } = sample;
         let mut sample = Sample::new(key_expr, value);
         sample.kind = kind.0;
         sample.timestamp = timestamp.map(|t| t.0);
+        sample.source_info.source_id = source_id;
+        sample.source_info.source_sn = source_sn;
         sample
     }
 }
@@ -280,6 +495,8 @@ pub struct _Reply {
     #[pyo3(get)]
     pub replier_id: _ZenohId,
     pub reply: Result<_Sample, _Value>,
+    #[pyo3(get)]
+    pub received_at: f64,
 }
 #[pymethods]
 impl _Reply {
@@ -288,6 +505,16 @@ impl _Reply {
         this
     }
     #[getter]
+    pub fn is_ok(&self) -> bool {
+        self.reply.is_ok()
+    }
+    pub fn matches_encoding(&self, encoding: &_Encoding) -> bool {
+        match &self.reply {
+            Ok(sample) => sample.matches_encoding(encoding),
+            Err(_) => false,
+        }
+    }
+    #[getter]
     pub fn ok(&self) -> PyResult<_Sample> {
         match &self.reply {
             Ok(o) => Ok(o.clone()),
@@ -301,15 +528,27 @@ impl _Reply {
             Ok(_) => Err(zenoh_core::zerror!("Called `Reply.err` on a non-err reply.").to_pyerr()),
         }
     }
+    #[getter]
+    pub fn value(&self) -> PyResult<_Value> {
+        match self.ok() {
+            Ok(sample) => Ok(sample.value()),
+            Err(_) => self.err(),
+        }
+    }
 }
 impl From<Reply> for _Reply {
     fn from(reply: Reply) -> Self {
+        let received_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
         _Reply {
             replier_id: _ZenohId(reply.replier_id),
             reply: match reply.sample {
                 Ok(o) => Ok(o.into()),
                 Err(e) => Err(e.into()),
             },
+            received_at,
         }
     }
 }
@@ -323,6 +562,23 @@ impl _Hello {
     pub fn pynew(this: Self) -> Self {
         this
     }
+    #[staticmethod]
+    pub fn new(whatami: _WhatAmI, zid: Option<&str>, locators: Vec<String>) -> PyResult<Self> {
+        let zid = zid
+            .map(str::parse::<ZenohId>)
+            .transpose()
+            .map_err(|e| zenoh_core::zerror!("Invalid ZenohId: {}", e).to_pyerr())?;
+        let locators = locators
+            .iter()
+            .map(|l| l.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|e| zenoh_core::zerror!("Invalid locator: {}", e).to_pyerr())?;
+        Ok(_Hello(Hello {
+            zid,
+            whatami: whatami.0,
+            locators,
+        }))
+    }
     #[getter]
     pub fn zid(&self) -> Option<_ZenohId> {
         self.0.zid.map(_ZenohId)
@@ -339,6 +595,42 @@ impl _Hello {
     pub fn locators(&self) -> Vec<String> {
         self.0.locators.iter().map(|l| l.to_string()).collect()
     }
+    pub fn merge(&self, other: &Self) -> Self {
+        let mut hello = self.0.clone();
+        for locator in &other.0.locators {
+            if !hello.locators.contains(locator) {
+                hello.locators.push(locator.clone());
+            }
+        }
+        _Hello(hello)
+    }
+    /// Hellos compare by `zid`, and those without one only equal themselves.
+    fn __richcmp__(&self, other: &Self, op: pyo3::pyclass::CompareOp) -> PyResult<bool> {
+        let eq = match (self.0.zid, other.0.zid) {
+            (Some(zid), Some(other)) => zid == other,
+            _ => std::ptr::eq(self, other),
+        };
+        match op {
+            pyo3::pyclass::CompareOp::Eq => Ok(eq),
+            pyo3::pyclass::CompareOp::Ne => Ok(!eq),
+            _ => Err(zenoh_core::zerror!("Hello does not support comparison").to_pyerr()),
+        }
+    }
+    fn __hash__(&self) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        match self.0.zid {
+            Some(zid) => zid.hash(&mut hasher),
+            None => (self as *const Self).hash(&mut hasher),
+        }
+        hasher.finish()
+    }
+    pub fn to_dict<'p>(&self, py: Python<'p>) -> PyResult<&'p PyDict> {
+        let dict = PyDict::new(py);
+        dict.set_item("zid", self.0.zid.map(|zid| zid.to_string()))?;
+        dict.set_item("whatami", self.whatami())?;
+        dict.set_item("locators", self.locators())?;
+        Ok(dict)
+    }
     pub fn __str__(&self) -> String {
         self.0.to_string()
     }
@@ -0,0 +1,877 @@
+# Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+
+# Contributors:
+#   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+# 
+import gc
+import json
+import time
+import traceback
+import warnings
+from threading import Thread
+import zenoh
+from zenoh import Encoding, Queue, SampleKind, QueryConsolidation, QueryTarget, Selector
+
+tab = "\t"
+ret = "\r\n"
+errors = []
+session = zenoh.open()
+
+def check_encoding_suffix():
+	key = "test/python/encoding-suffix"
+	sub = session.declare_subscriber(key, Queue())
+	time.sleep(0.5)
+	session.put(key, b'{"version": 2}', encoding=Encoding.from_str("application/json;schema=v2"))
+	sample = sub.receiver.get(timeout=5)
+	assert str(sample.encoding) == "application/json;schema=v2", f"unexpected encoding {sample.encoding}"
+	assert sample.value.encoding_suffix == ";schema=v2", f"unexpected suffix {sample.value.encoding_suffix}"
+	sub.undeclare()
+
+def check_reply_kind():
+	key = "test/python/reply-kind"
+	store = {}
+	def on_sample(sample):
+		store[str(sample.key_expr)] = sample
+	def on_query(query):
+		for sample in list(store.values()):
+			if query.key_expr.intersects(sample.key_expr):
+				query.reply(sample)
+	sub = session.declare_subscriber(key, on_sample)
+	queryable = session.declare_queryable(key, on_query)
+	time.sleep(0.5)
+	session.put(key, "value")
+	session.delete(key)
+	time.sleep(0.5)
+	replies = list(session.get(key, Queue()))
+	assert len(replies) == 1, f"expected 1 reply, got {len(replies)}"
+	assert replies[0].ok.kind == SampleKind.DELETE(), f"unexpected kind {replies[0].ok.kind}"
+	sub.undeclare()
+	queryable.undeclare()
+
+def check_concurrent_declarations():
+	threads_count = 8
+	rounds = 20
+	failures = []
+	def worker(index):
+		try:
+			for round in range(rounds):
+				key = f"test/python/concurrent/{index}/{round}"
+				received = Queue()
+				sub = session.declare_subscriber(key, received)
+				queryable = session.declare_queryable(key, lambda query: query.reply(zenoh.Sample(query.key_expr, "reply")))
+				session.put(key, "value")
+				replies = list(session.get(key, Queue(), timeout=5))
+				assert len(replies) == 1, f"expected 1 reply on {key}, got {len(replies)}"
+				sub.undeclare()
+				queryable.undeclare()
+				received.close()
+		except Exception as e:
+			failures.append(e)
+	threads = [Thread(target=worker, args=(i,)) for i in range(threads_count)]
+	for thread in threads:
+		thread.start()
+	for thread in threads:
+		thread.join(timeout=60)
+		assert not thread.is_alive(), "a worker thread is deadlocked"
+	assert not failures, f"{len(failures)} workers failed, first with {failures[0]!r}"
+
+def check_consolidation():
+	prefix = "test/python/consolidation"
+	ts_session = zenoh.open({"timestamping": {"enabled": True}})
+	versions = []
+	def on_sample(sample):
+		versions.append(sample)
+	def on_query(query):
+		# Replying oldest first makes every reply newer than the previous one, which monotonic consolidation keeps.
+		oldest_first = query.decode_parameters().get("order") == "oldest"
+		for sample in sorted(versions, key=lambda s: s.timestamp, reverse=not oldest_first):
+			query.reply(sample)
+	sub = ts_session.declare_subscriber(f"{prefix}/*", on_sample)
+	queryable = ts_session.declare_queryable(f"{prefix}/*", on_query)
+	time.sleep(0.5)
+	for i in range(3):
+		for key in ["a", "b"]:
+			ts_session.put(f"{prefix}/{key}", f"v{i}")
+			time.sleep(0.1)
+	time.sleep(0.5)
+	assert len(versions) == 6 and all(s.timestamp is not None for s in versions), "storage should hold 6 timestamped versions"
+	everything = sorted(f"{key}={v}" for key in "ab" for v in ["v0", "v1", "v2"])
+	latest = ["a=v2", "b=v2"]
+	expectations = [
+		(QueryConsolidation.NONE(), "newest", everything),
+		(QueryConsolidation.NONE(), "oldest", everything),
+		(QueryConsolidation.MONOTONIC(), "newest", latest),
+		(QueryConsolidation.MONOTONIC(), "oldest", everything),
+		(QueryConsolidation.LATEST(), "newest", latest),
+		(QueryConsolidation.LATEST(), "oldest", latest),
+		(QueryConsolidation.AUTO(), "newest", latest),
+		(QueryConsolidation.AUTO(), "oldest", latest),
+	]
+	for consolidation, order, expected in expectations:
+		replies = list(ts_session.get(f"{prefix}/*", Queue(), consolidation=consolidation, parameters={"order": order}))
+		values = sorted(f"{str(reply.ok.key_expr).rsplit('/', 1)[1]}={reply.ok.payload.decode()}" for reply in replies)
+		assert values == expected, f"expected {expected} with consolidation {consolidation} and {order} first, got {values}"
+	sub.undeclare()
+	queryable.undeclare()
+	ts_session.close()
+
+def check_reply_to_dict_malformed():
+	key = "test/python/reply-to-dict-malformed"
+	payloads = {f"{key}/json": (b"{not json", Encoding.APP_JSON()), f"{key}/text": (b"\xff\xfe", Encoding.TEXT_PLAIN())}
+	def on_query(query):
+		for k, (payload, encoding) in payloads.items():
+			query.reply(zenoh.Sample(k, zenoh.Value(payload, encoding)))
+	queryable = session.declare_queryable(f"{key}/*", on_query)
+	time.sleep(0.5)
+	replies = session.get_list(f"{key}/*")
+	assert len(replies) == 2, f"expected 2 replies, got {len(replies)}"
+	for reply in replies:
+		d = reply.to_dict()
+		assert d["value"] == payloads[d["key"]][0], f"undecodable value should fall back to bytes, got {d}"
+	queryable.undeclare()
+
+def check_get_encoding_filter():
+	key = "test/python/get-encoding"
+	encodings = {
+		"json": Encoding.APP_JSON(),
+		"json-v2": Encoding.from_str("application/json;schema=v2"),
+		"text": Encoding.TEXT_PLAIN(),
+	}
+	def on_query(query):
+		for name, encoding in encodings.items():
+			query.reply(zenoh.Sample(f"{key}/{name}", zenoh.Value(b"{}", encoding)))
+	queryable = session.declare_queryable(f"{key}/*", on_query)
+	time.sleep(0.5)
+	expectations = [
+		(None, ["json", "json-v2", "text"]),
+		(Encoding.APP_JSON(), ["json"]),
+		(Encoding.from_str("application/json;schema=v2"), ["json-v2"]),
+		(Encoding.TEXT_PLAIN(), ["text"]),
+		(Encoding.APP_INTEGER(), []),
+	]
+	for encoding, expected in expectations:
+		replies = list(session.get(f"{key}/*", Queue(), encoding=encoding, consolidation=QueryConsolidation.NONE()))
+		names = sorted(str(reply.ok.key_expr).rsplit("/", 1)[1] for reply in replies)
+		assert names == expected, f"expected {expected} with encoding {encoding}, got {names}"
+	queryable.undeclare()
+
+def check_selector_parameters():
+	key = "test/python/selector-parameters"
+	parameters = {"q": "a&b=c d", "eq": "x=y", "space key": "  ", "empty": "", "percent": "100%"}
+	selector = Selector(key).with_parameters(parameters)
+	assert str(selector.key_expr) == key, f"unexpected key expression {selector.key_expr}"
+	assert selector.decode_parameters() == parameters, f"unexpected parameters {selector.decode_parameters()}"
+	assert Selector(str(selector)).decode_parameters() == parameters, f"parameters didn't survive formatting {selector}"
+	selector.parameters = "a=1&b=2"
+	assert selector.decode_parameters() == {"a": "1", "b": "2"}, f"unexpected parameters {selector.parameters}"
+	received = []
+	def on_query(query):
+		received.append(query.decode_parameters())
+		query.reply(zenoh.Sample(query.key_expr, "ok"))
+	queryable = session.declare_queryable(key, on_query)
+	time.sleep(0.5)
+	list(session.get(Selector(key).with_parameters(parameters), Queue()))
+	assert received == [parameters], f"queryable received {received}"
+	queryable.undeclare()
+
+def check_matches_encoding():
+	v2 = Encoding.from_str("application/json;schema=v2")
+	sample = zenoh.Sample("test/python/matches-encoding", zenoh.Value(b"{}", v2))
+	assert sample.matches_encoding(Encoding.from_str("application/json;schema=v2")), "same encoding should match"
+	assert not sample.matches_encoding(Encoding.APP_JSON()), "missing suffix shouldn't match"
+	assert not sample.matches_encoding(Encoding.from_str("application/json;schema=v1")), "other suffix shouldn't match"
+	assert not sample.matches_encoding(Encoding.from_str("text/json;schema=v2")), "other prefix shouldn't match"
+	plain = zenoh.Sample("test/python/matches-encoding", zenoh.Value(b"{}", Encoding.APP_JSON()))
+	assert plain.matches_encoding(Encoding.from_str("application/json")), "parsed encoding should match"
+	suffixed = Encoding.APP_JSON()
+	suffixed.append("")
+	assert plain.matches_encoding(suffixed), "empty suffix should match no suffix"
+
+def check_pinned_id():
+	zid = "a1b2c3d4e5f60718"
+	config = zenoh.Config()
+	config.set_id(zid)
+	pinned = zenoh.open(config)
+	assert str(pinned.info().zid()) == zid, f"unexpected zid {pinned.info().zid()}"
+	pinned.close()
+	for invalid in ["not-hex", "a1" * 17]:
+		try:
+			zenoh.Config().set_id(invalid)
+		except zenoh.ZError:
+			continue
+		raise AssertionError(f"`{invalid}` should have been rejected")
+
+def check_sample_kind_consistency():
+	key = "test/python/sample-kind-consistency"
+	sub = session.declare_subscriber(key, Queue())
+	time.sleep(0.5)
+	for operation, kind in [(lambda **kw: session.put(key, "value", **kw), SampleKind.DELETE()), (lambda **kw: session.delete(key, **kw), SampleKind.PUT())]:
+		try:
+			operation(kind=kind)
+		except zenoh.ZError:
+			continue
+		raise AssertionError(f"mismatched kind {kind} should have been rejected")
+	session.put(key, "value", kind=SampleKind.PUT())
+	session.delete(key, kind=SampleKind.DELETE())
+	with warnings.catch_warnings(record=True) as caught:
+		warnings.simplefilter("always")
+		session.put(key, "value", sample_kind=SampleKind.PUT())
+	assert any(issubclass(w.category, DeprecationWarning) for w in caught), "`sample_kind` should emit a DeprecationWarning"
+	kinds = [sub.receiver.get(timeout=5).kind for _ in range(3)]
+	assert kinds == [SampleKind.PUT(), SampleKind.DELETE(), SampleKind.PUT()], f"unexpected kinds {kinds}"
+	assert sub.receiver.try_recv() is None, "rejected operations shouldn't have been published"
+	sub.undeclare()
+
+def check_queue_bound_alias():
+	with warnings.catch_warnings(record=True) as caught:
+		warnings.simplefilter("always")
+		queue = Queue(bound=1, on_full="drop_latest")
+	assert any(issubclass(w.category, DeprecationWarning) for w in caught), "`bound` should emit a DeprecationWarning"
+	queue.put("kept")
+	queue.put("dropped")
+	assert queue.dropped_count() == 1, "`bound` should set the queue's capacity"
+	assert queue.try_recv() == "kept", "the first element should have been kept"
+
+def check_undeclare_keyexpr():
+	declared = session.stats()["keyexprs_declared"]
+	for i in range(1000):
+		keyexpr = session.declare_keyexpr(f"test/python/undeclare-keyexpr/{i}")
+		assert session.stats()["keyexprs_declared"] == declared + 1, f"unexpected stats {session.stats()}"
+		session.undeclare_keyexpr(keyexpr)
+		# Zenoh only fails to undeclare a key expression once it has removed it from the session's table.
+		try:
+			session.undeclare_keyexpr(keyexpr)
+		except zenoh.ZError:
+			pass
+		else:
+			raise AssertionError(f"`{keyexpr}` was still declared in the session after being undeclared")
+	assert session.stats()["keyexprs_declared"] == declared, f"declarations leaked: {session.stats()}"
+	try:
+		session.undeclare_keyexpr(zenoh.KeyExpr("test/python/undeclare-keyexpr/never-declared"))
+	except zenoh.ZError:
+		pass
+	else:
+		raise AssertionError("undeclaring a key expression that wasn't declared should fail")
+	assert session.stats()["keyexprs_declared"] == declared, f"failed undeclaration was counted: {session.stats()}"
+
+def check_query_target():
+	key = "test/python/query-target"
+	complete = session.declare_queryable(key, lambda query: query.reply(zenoh.Sample(query.key_expr, "complete")), complete=True)
+	partial = session.declare_queryable(key, lambda query: query.reply(zenoh.Sample(query.key_expr, "partial")), complete=False)
+	time.sleep(0.5)
+	expectations = [
+		(QueryTarget.ALL(), ["complete", "partial"]),
+		(QueryTarget.ALL_COMPLETE(), ["complete"]),
+	]
+	for target, expected in expectations:
+		replies = list(session.get(key, Queue(), target=target, consolidation=QueryConsolidation.NONE()))
+		values = sorted(reply.ok.payload.decode() for reply in replies)
+		assert values == expected, f"expected {expected} with target {target}, got {values}"
+	assert [str(t) for t in [QueryTarget.BEST_MATCHING(), QueryTarget.ALL(), QueryTarget.ALL_COMPLETE()]] == ["BEST_MATCHING", "ALL", "ALL_COMPLETE"]
+	complete.undeclare()
+	partial.undeclare()
+
+def check_query_accept():
+	key = "test/python/query-accept"
+	accepted = []
+	queryable = session.declare_queryable(key, lambda query: accepted.append([str(e) for e in query.accept]))
+	time.sleep(0.5)
+	session.get_list(key, parameters="_accept= application/json , ,text/plain,")
+	assert accepted == [["application/json", "text/plain"]], f"unexpected accepted encodings {accepted}"
+	queryable.undeclare()
+
+def check_config_merge():
+	base = zenoh.Config()
+	base.insert_json5("connect/endpoints", '["tcp/127.0.0.1:7447", "tcp/127.0.0.1:7448"]')
+	base.insert_json5("scouting/multicast/enabled", "false")
+	patch = zenoh.Config()
+	patch.insert_json5("connect/endpoints", '["tcp/127.0.0.1:7449"]')
+	patch.insert_json5("mode", '"client"')
+	diff = base.diff(patch)
+	assert diff == ["/connect/endpoints", "/mode", "/scouting/multicast/enabled"], f"unexpected diff {diff}"
+	base.merge(patch)
+	assert json.loads(base.get_json("connect/endpoints")) == ["tcp/127.0.0.1:7449"], "arrays should be replaced"
+	assert json.loads(base.get_json("mode")) == "client", "changed fields should be merged"
+	assert json.loads(base.get_json("scouting/multicast/enabled")) == False, "default fields shouldn't override"
+	assert base.diff(patch) == ["/scouting/multicast/enabled"], f"unexpected diff after merge {base.diff(patch)}"
+	client = zenoh.Config.client(["tcp/127.0.0.1:7447"])
+	client.merge(zenoh.Config.from_obj({"mode": "peer"}))
+	assert json.loads(client.get_json("mode")) == "peer", "explicitly set default values should be merged"
+	assert json.loads(client.get_json("connect/endpoints")) == ["tcp/127.0.0.1:7447"], "unset fields shouldn't override"
+
+def check_default_qos_overrides():
+	key = "test/python/default-qos"
+	high, low = zenoh.Priority.REAL_TIME(), zenoh.Priority.BACKGROUND()
+	block, drop = zenoh.CongestionControl.BLOCK(), zenoh.CongestionControl.DROP()
+	other = zenoh.open()
+	other.set_default_qos(priority=low, congestion_control=block)
+	# Puts, deletes and publishers resolve their QoS alike, but only publishers report it.
+	cases = [
+		({}, low, block),
+		({"priority": high}, high, block),
+		({"congestion_control": drop}, low, drop),
+		({"priority": high, "congestion_control": drop}, high, drop),
+	]
+	for kwargs, priority, congestion_control in cases:
+		publisher = other.declare_publisher(key, **kwargs)
+		assert (publisher.priority, publisher.congestion_control) == (priority, congestion_control), f"unexpected QoS with {kwargs}: {publisher.priority}, {publisher.congestion_control}"
+		publisher.undeclare()
+	sub = other.declare_subscriber(key, Queue())
+	queryable = other.declare_queryable(key, lambda query: query.reply(zenoh.Sample(key, "reply")))
+	time.sleep(0.5)
+	for kwargs, _, _ in cases:
+		other.put(key, "value", **kwargs)
+		other.delete(key, **kwargs)
+	time.sleep(0.5)
+	samples = sub.receiver.drain()
+	assert [s.kind for s in samples] == [SampleKind.PUT(), SampleKind.DELETE()] * len(cases), f"unexpected samples {samples}"
+	replies = other.get_list(key)
+	assert [r.ok.payload for r in replies] == [b"reply"], "queries shouldn't be affected by the default QoS"
+	sub.undeclare()
+	queryable.undeclare()
+	other.close()
+
+def check_config_from_env_errors():
+	invalid = {
+		"ZENOH_MODE": "banana",
+		"ZENOH_CONNECT": "tcp/127.0.0.1:7447,not-an-endpoint",
+		"ZENOH_LISTEN": "not-an-endpoint",
+		"ZENOH_ID": "not-hex",
+	}
+	for name, value in invalid.items():
+		try:
+			zenoh.Config.from_env(environ={name: value})
+		except zenoh.ZError as e:
+			assert name in str(e) and value in str(e), f"the error should name `{name}` and its value, got {e}"
+			continue
+		raise AssertionError(f"`{name}={value}` should have been rejected")
+	config = zenoh.Config.from_env(environ={"ZENOH_MODE": "client", "ZENOH_CONNECT": " tcp/127.0.0.1:7447 , ", "ZENOH_LISTEN": ""})
+	assert json.loads(config.get_json("mode")) == "client", f"unexpected mode {config.get_json('mode')}"
+	assert json.loads(config.get_json("connect/endpoints")) == ["tcp/127.0.0.1:7447"], f"unexpected endpoints {config.get_json('connect/endpoints')}"
+
+def check_put_validate_only():
+	key = "test/python/validate-only"
+	sub = session.declare_subscriber(key, Queue())
+	time.sleep(0.5)
+	puts_sent = session.stats()["puts_sent"]
+	size = session.put(key, "validated", validate_only=True, return_size=True)
+	assert size == len("validated"), f"unexpected size {size}"
+	for kwargs in [{"keyexpr": "test//python"}, {"kind": SampleKind.DELETE()}]:
+		try:
+			session.put(**{"keyexpr": key, "value": "invalid", **kwargs}, validate_only=True)
+		except zenoh.ZError:
+			continue
+		raise AssertionError(f"validating with {kwargs} should raise the same error as putting")
+	time.sleep(0.5)
+	assert sub.receiver.drain() == [], "validated puts shouldn't be published"
+	assert session.stats()["puts_sent"] == puts_sent, f"validated puts shouldn't be counted: {session.stats()}"
+	sub.undeclare()
+
+def check_put_stream_errors():
+	key = "test/python/put-stream-errors"
+	sub = session.declare_subscriber(key, Queue())
+	time.sleep(0.5)
+	assert session.put_stream(key, iter(["a", "b"])) == 2, "put_stream should return how many values were published"
+	try:
+		session.put_stream(key, iter(["c", "d", object(), "e"]))
+	except zenoh.ZError as e:
+		assert "after 2 values" in str(e), f"the error should tell how many values were published, got {e}"
+	else:
+		raise AssertionError("an invalid value should stop the stream with a ZError")
+	time.sleep(0.5)
+	payloads = [sample.payload for sample in sub.receiver.drain()]
+	assert payloads == [b"a", b"b", b"c", b"d"], f"the stream should stop at the invalid value, got {payloads}"
+	sub.undeclare()
+
+def check_shared_publisher():
+	key = "test/python/shared-publisher"
+	publisher = session.declare_publisher(key)
+	other = zenoh.Publisher(zenoh.zenoh._Publisher(publisher._inner_))
+	publisher.put("value")
+	other.put("value")
+	assert publisher.is_valid() and other.is_valid(), "both handles should be valid"
+	publisher.undeclare()
+	assert not publisher.is_valid() and not other.is_valid(), "undeclaring should invalidate every handle"
+	for handle in [publisher, other]:
+		for operation in [lambda: handle.put("value"), handle.delete]:
+			try:
+				operation()
+			except zenoh.ZError:
+				continue
+			raise AssertionError("using an undeclared publisher should fail")
+	assert str(other.key_expr) == key, f"unexpected key expression {other.key_expr}"
+	assert other.stats()["puts_sent"] == 2, f"unexpected stats {other.stats()}"
+	other.undeclare()
+
+def check_publisher_delete_qos():
+	key = "test/python/publisher-delete-qos"
+	sub = session.declare_subscriber(key, Queue())
+	default = session.declare_publisher(key)
+	assert default.priority == zenoh.Priority.DATA() and default.congestion_control == zenoh.CongestionControl.DROP(), "publishers should default to Zenoh's QoS"
+	publisher = session.declare_publisher(key, priority=zenoh.Priority.INTERACTIVE_HIGH(), congestion_control=zenoh.CongestionControl.BLOCK())
+	assert publisher.priority == zenoh.Priority.INTERACTIVE_HIGH(), f"unexpected priority {publisher.priority}"
+	assert publisher.congestion_control == zenoh.CongestionControl.BLOCK(), f"unexpected congestion control {publisher.congestion_control}"
+	time.sleep(0.5)
+	publisher.delete()
+	assert sub.receiver.get(timeout=5).kind == SampleKind.DELETE(), "the deletion should be received"
+	for option, error in [("attachment", NotImplementedError), ("timestamp", NotImplementedError), ("priority", TypeError)]:
+		try:
+			publisher.delete(**{option: b"unsupported"})
+		except error:
+			continue
+		raise AssertionError(f"`{option}` should raise a {error.__name__}")
+	default.undeclare()
+	publisher.undeclare()
+	sub.undeclare()
+
+def check_keyexpr_errors():
+	expectations = {
+		"": "can't be empty",
+		"a//b": "empty chunk at position 2",
+		"/a": "empty chunk at position 0",
+		"a/b/": "empty chunk at position 4",
+		"a/#": "`#` at position 2 is reserved",
+		"a/b?x=1": "`?` at position 3",
+		"a/$b": "`$` at position 2",
+		"a/b*": "chunk `b*` at position 2 mixes `*`",
+	}
+	for expr, expected in expectations.items():
+		try:
+			zenoh.KeyExpr(expr)
+		except zenoh.ZError as e:
+			assert expected in str(e), f"unexpected error for `{expr}`: {e}"
+			continue
+		raise AssertionError(f"`{expr}` should have been rejected")
+	for expr in ["a/*/b", "a/**", "a/b$*c"]:
+		zenoh.KeyExpr(expr)
+	chunks = zenoh.KeyExpr("a/*/b$*c/**").chunks()
+	assert chunks == ["a", "*", "b$*c", "**"], f"unexpected chunks {chunks}"
+	kinds = [(chunk.kind, chunk.is_wild) for chunk in chunks]
+	assert kinds == [("literal", False), ("wild", True), ("partial", True), ("double_wild", True)], f"unexpected kinds {kinds}"
+	chunks = zenoh.KeyExpr.autocanonize("a/**/**/b").chunks()
+	assert chunks == ["a", "**", "b"], f"unexpected chunks {chunks}"
+
+def check_keyexpr_wildcards():
+	for expr, wild in [("a/b", False), ("a/*", True), ("a/**", True), ("a/b$*", True), ("a/b$*c/d", True)]:
+		assert zenoh.KeyExpr.has_wildcards(expr) == wild, f"`{expr}` has_wildcards should be {wild}"
+		assert zenoh.KeyExpr(expr).is_wild() == wild, f"`{expr}` is_wild should be {wild}"
+	assert zenoh.KeyExpr.has_wildcards("a//*"), "has_wildcards shouldn't validate its argument"
+	keyexpr = zenoh.KeyExpr.try_from("a/b$*")
+	assert isinstance(keyexpr, zenoh.KeyExpr) and str(keyexpr) == "a/b$*", f"unexpected key expression {keyexpr!r}"
+	error = zenoh.KeyExpr.try_from("a//b")
+	assert isinstance(error, zenoh.ZError) and "empty chunk" in str(error), f"try_from should return the error, got {error!r}"
+
+def check_keyexpr_strip_prefix():
+	expectations = [
+		("a/b/c", "a/b", "c"),
+		("a/b/c/d", "a", "b/c/d"),
+		("a/bc", "a/b", None),
+		("a/b", "a/b", None),
+		("a/b", "a/b/c", None),
+		("a/*/c", "a/*", "c"),
+		("a/b/c", "a/*", None),
+	]
+	for expr, prefix, expected in expectations:
+		remainder = zenoh.KeyExpr(expr).strip_prefix(prefix)
+		remainder = None if remainder is None else str(remainder)
+		assert remainder == expected, f"expected `{expr}` stripped of `{prefix}` to give {expected!r}, got {remainder!r}"
+
+def check_subscriber_errors():
+	key = "test/python/subscriber-errors"
+	received = []
+	failed = []
+	def on_sample(sample):
+		received.append(sample.payload)
+		if len(received) % 3 == 0:
+			raise ValueError(sample.payload)
+	def on_error(sample, e):
+		assert isinstance(e, ValueError) and e.args[0] == sample.payload, f"unexpected error {e!r}"
+		failed.append(sample.payload)
+	logged = []
+	def on_logged_sample(sample):
+		logged.append(sample.payload)
+		raise ValueError(sample.payload)
+	inflight_received = []
+	inflight_failed = []
+	def on_inflight_sample(sample):
+		inflight_received.append(sample.payload)
+		if int(sample.payload) % 3 == 2:
+			raise ValueError(sample.payload)
+	sub = session.declare_subscriber(key, on_sample, on_error=on_error)
+	logging_sub = session.declare_subscriber(key, on_logged_sample)
+	inflight_sub = session.declare_subscriber(key, on_inflight_sample, inflight=2, on_error=lambda sample, e: inflight_failed.append(sample.payload))
+	time.sleep(0.5)
+	for i in range(9):
+		session.put(key, str(i))
+	time.sleep(1)
+	assert len(received) == 9, f"expected 9 samples, got {len(received)}"
+	assert failed == [b"2", b"5", b"8"], f"unexpected failures {failed}"
+	assert len(logged) == 9, f"subscribers without `on_error` should log and skip failures, got {len(logged)} samples"
+	assert len(inflight_received) == 9, f"expected 9 samples with `inflight`, got {len(inflight_received)}"
+	assert sorted(inflight_failed) == [b"2", b"5", b"8"], f"unexpected failures with `inflight` {inflight_failed}"
+	sub.undeclare()
+	logging_sub.undeclare()
+	inflight_sub.undeclare()
+
+def check_multi_key_subscriber():
+	prefix = "test/python/multi-key"
+	sub = session.declare_subscriber([f"{prefix}/a", f"{prefix}/b"], Queue())
+	received = []
+	callback_sub = session.declare_subscriber([f"{prefix}/a", f"{prefix}/b"], lambda sample: received.append(str(sample.key_expr)))
+	time.sleep(0.5)
+	for key in ["a", "b", "c"]:
+		session.put(f"{prefix}/{key}", key)
+	time.sleep(0.5)
+	sub.undeclare()
+	callback_sub.undeclare()
+	# The queue is only closed once every key's subscriber has been undeclared.
+	keys = sorted(str(sample.key_expr) for sample in sub.receiver.get_remaining(timeout=5))
+	assert keys == [f"{prefix}/a", f"{prefix}/b"], f"unexpected samples {keys}"
+	assert sorted(received) == keys, f"unexpected samples {received}"
+	session.put(f"{prefix}/a", "a")
+	session.put(f"{prefix}/b", "b")
+	time.sleep(0.5)
+	assert len(received) == 2, f"undeclared subscribers still received samples: {received}"
+
+def check_value_equality():
+	assert zenoh.Value("value") == zenoh.Value(b"value", Encoding.TEXT_PLAIN()), "equal values should compare equal"
+	assert zenoh.Value("1") != zenoh.Value(1), "values with different encodings should differ"
+	assert zenoh.Value(b"a") != zenoh.Value(b"b"), "values with different payloads should differ"
+	assert len({zenoh.Value("value"), zenoh.Value("value"), zenoh.Value(b"value")}) == 2, "equal values should hash alike"
+
+def check_whatami_hash():
+	roles = {zenoh.WhatAmI.PEER(), zenoh.WhatAmI.PEER(), zenoh.WhatAmI.ROUTER()}
+	assert len(roles) == 2, f"equal roles should hash alike, got {roles}"
+	assert {zenoh.WhatAmI.CLIENT(): "client"}[zenoh.WhatAmI.CLIENT()] == "client", "roles should be usable as dictionary keys"
+
+def check_hello_identity():
+	zid = "a1b2c3d4"
+	first = zenoh.Hello(zenoh.WhatAmI.PEER(), zid, ["tcp/127.0.0.1:7447"])
+	same = zenoh.Hello(zenoh.WhatAmI.PEER(), zid, ["udp/127.0.0.1:7447"])
+	anonymous = zenoh.Hello(zenoh.WhatAmI.PEER())
+	other_anonymous = zenoh.Hello(zenoh.WhatAmI.PEER())
+	assert first == same and hash(first) == hash(same), "hellos with the same zid should be equal"
+	assert anonymous == anonymous and anonymous != other_anonymous, "zid-less hellos should only equal themselves"
+	assert len({first, same, anonymous, other_anonymous}) == 3, "sets should follow the same rule as =="
+	other = zenoh.Hello(zenoh.WhatAmI.ROUTER(), "e5f6")
+	deduped = zenoh.Hello.dedup([anonymous, first, other, same, other_anonymous])
+	assert deduped == [anonymous, first, other, other_anonymous], f"dedup should keep the first-seen order, got {deduped}"
+	assert deduped[1].locators == ["tcp/127.0.0.1:7447", "udp/127.0.0.1:7447"], f"unexpected locators {deduped[1].locators}"
+	assert deduped[0] is anonymous and deduped[3] is other_anonymous, "zid-less hellos should be kept as is"
+
+def check_parse_encoding():
+	expectations = {
+		"application/json": "application/json",
+		" Application/JSON ": "application/json",
+		"application/json;schema=v2": "application/json;schema=v2",
+		"TEXT/Plain ; Charset=UTF-8": "text/plain;charset=UTF-8",
+		"application/yaml": "application/yaml",
+		"": "",
+	}
+	for mime, expected in expectations.items():
+		encoding = zenoh.parse_encoding(mime)
+		assert str(encoding) == expected, f"`{mime}` parsed into `{encoding}`"
+	assert zenoh.parse_encoding("application/json; schema=v2").suffix == ";schema=v2", "the parameters should be the suffix"
+	assert zenoh.parse_encoding("image/png") == Encoding.IMAGE_PNG(), "known types should parse into known encodings"
+	for mime in ["json", "application/", "/json", "application/json;", "application/json;schema", "text/plain;=v", "a b/c", ";a=b"]:
+		try:
+			zenoh.parse_encoding(mime)
+		except zenoh.ZError:
+			continue
+		raise AssertionError(f"`{mime}` should have been rejected")
+	assert len(Encoding.known()) == 21, f"unexpected known encodings {Encoding.known()}"
+
+def check_latest_value_cache():
+	prefix = "test/python/latest-value"
+	sub = session.declare_subscriber(f"{prefix}/**", zenoh.LatestValueCache())
+	time.sleep(0.5)
+	for i in range(3):
+		session.put(f"{prefix}/a", str(i))
+	session.put(f"{prefix}/b", "b")
+	session.put(f"{prefix}/c", "c")
+	session.delete(f"{prefix}/c")
+	time.sleep(1)
+	assert sub.get_current(f"{prefix}/a").payload == b"2", f"unexpected sample {sub.get_current(f'{prefix}/a')}"
+	assert sub.get_current(f"{prefix}/c") is None, "deleted keys should leave the cache"
+	snapshot = sub.snapshot()
+	assert sorted(snapshot) == [f"{prefix}/a", f"{prefix}/b"], f"unexpected snapshot {snapshot}"
+	sub.undeclare()
+	for extract in ["value", "bytes"]:
+		try:
+			session.declare_subscriber(f"{prefix}/**", zenoh.LatestValueCache(), extract=extract)
+		except ValueError:
+			continue
+		raise AssertionError(f"`LatestValueCache` with `extract={extract!r}` should be rejected")
+
+def check_close_with_children():
+	key = "test/python/close-with-children"
+	other = zenoh.open()
+	sub = other.declare_subscriber(key, Queue())
+	queryable = other.declare_queryable(key)
+	publisher = other.declare_publisher(key)
+	time.sleep(0.5)
+	publisher.put("value")
+	assert sub.receiver.get(timeout=5).payload == b"value", "the subscriber should receive the value before closing"
+	other.close()
+	assert not publisher.is_valid(), "closing should undeclare publishers"
+	for receiver in [sub.receiver, queryable.receiver]:
+		try:
+			receiver.get(timeout=5)
+		except StopIteration:
+			continue
+		raise AssertionError("closing should close the handlers of subscribers and queryables")
+	try:
+		other.declare_subscriber(key, Queue())
+	except zenoh.ZError:
+		pass
+	else:
+		raise AssertionError("declaring on a closed session should fail")
+	sub.undeclare()
+	other.close()
+
+def check_invalid_durations():
+	for operation in [
+		lambda: zenoh.open_retry(attempts=1, delay=-1.0),
+		lambda: zenoh.open_retry(attempts=1, delay=float("nan")),
+		lambda: session.get("test/python/invalid-durations", Queue(), timeout=-1.0),
+		lambda: session.get("test/python/invalid-durations", Queue(), timeout=float("inf")),
+	]:
+		try:
+			operation()
+		except ValueError:
+			continue
+		raise AssertionError("invalid durations should raise a ValueError")
+
+def check_queue_invalid_timeouts():
+	queue = Queue()
+	for operation in [
+		lambda: queue.recv_timeout(-1),
+		lambda: queue.recv_timeout(float("nan")),
+		lambda: queue.get(timeout=float("inf")),
+		lambda: queue.get_remaining(timeout=-1),
+	]:
+		try:
+			operation()
+		except ValueError:
+			continue
+		raise AssertionError("invalid queue timeouts should raise a ValueError")
+
+def check_recv_batch_arguments():
+	queue = Queue()
+	queue.put("not consumed")
+	for kwargs in [{"max_size": 0}, {"max_latency": -1.0}, {"max_latency": float("nan")}]:
+		try:
+			queue.recv_batch(**kwargs)
+		except ValueError:
+			continue
+		raise AssertionError(f"recv_batch should reject {kwargs}")
+	assert queue.drain() == ["not consumed"], "invalid arguments shouldn't consume any element"
+
+def check_query_timeout():
+	key = "test/python/query-timeout"
+	replies = session.get_list(f"{key}/nobody")
+	assert replies.completed, "a query nobody answers should complete"
+	def on_query(query):
+		time.sleep(2)
+		query.reply(zenoh.Sample(query.key_expr, "late"))
+	queryable = session.declare_queryable(key, on_query)
+	time.sleep(0.5)
+	replies = session.get_list(key, timeout=0.5)
+	assert not replies.completed, "a query whose queryable is too slow should time out"
+	assert all(not r.is_ok for r in replies), f"unexpected replies {replies}"
+	queryable.undeclare()
+
+def check_codec_put():
+	key = "test/python/codec-put"
+	encoding = Encoding.from_str("application/x-test-point")
+	class Point:
+		def __init__(self, x, y):
+			self.x, self.y = x, y
+	def decode(payload):
+		return Point(*(int(c) for c in payload.decode().split(",")))
+	zenoh.register_codec(encoding, lambda point: f"{point.x},{point.y}".encode(), decode)
+	sub = session.declare_subscriber(key, Queue())
+	publisher = session.declare_publisher(key, encoding=encoding)
+	time.sleep(0.5)
+	session.put(key, Point(1, 2), encoding=encoding)
+	publisher.put(Point(3, 4))
+	for expected in [b"1,2", b"3,4"]:
+		sample = sub.receiver.get(timeout=5)
+		assert sample.payload == expected, f"unexpected payload {sample.payload}"
+		assert str(sample.encoding) == str(encoding), f"unexpected encoding {sample.encoding}"
+		point = sample.value.decode()
+		assert f"{point.x},{point.y}".encode() == expected, "the registered decoder should be used"
+	publisher.undeclare()
+	sub.undeclare()
+
+def check_payload_slices_lifetime():
+	views = zenoh.Value.from_str("payload slices").payload_slices()
+	gc.collect()
+	assert b"".join(bytes(view) for view in views) == b"payload slices", "views should outlive their value"
+	key = "test/python/payload-slices"
+	kept = []
+	sub = session.declare_subscriber(key, lambda sample: kept.extend(sample.payload_slices()))
+	time.sleep(0.5)
+	session.put(key, b"kept after the callback")
+	time.sleep(1)
+	sub.undeclare()
+	gc.collect()
+	assert b"".join(bytes(view) for view in kept) == b"kept after the callback", "views should outlive their sample"
+	assert all(view.readonly for view in kept), "views should be read-only"
+
+def check_queryable_recv_type():
+	key = "test/python/queryable-recv-type"
+	queryable = session.declare_queryable(key)
+	time.sleep(0.5)
+	replies = session.get(key, Queue())
+	query = queryable.recv(timeout=5)
+	assert isinstance(query, zenoh.Query), f"queryable.recv() returned a {type(query)}, not a zenoh.Query"
+	assert query.reply_all([(key, "reply")], encoding=Encoding.APP_JSON()) == 1, "the Python-only methods of Query should be available"
+	query = None
+	reply = replies.get(timeout=5)
+	assert isinstance(reply, zenoh.Reply), f"the replies' queue received a {type(reply)}, not a zenoh.Reply"
+	assert str(reply.ok.encoding) == str(Encoding.APP_JSON()), f"unexpected encoding {reply.ok.encoding}"
+	queryable.undeclare()
+
+def check_get_return_types():
+	key = "test/python/get-return-types"
+	queue = Queue()
+	assert session.get(key, queue) is queue, "get should return the handler's receiver"
+	queryable = session.declare_queryable(key)
+	time.sleep(0.5)
+	handle = session.get_handle(key, Queue(), timeout=10)
+	assert isinstance(handle, zenoh.QueryHandle), f"get_handle returned a {type(handle)}"
+	queryable.recv(timeout=5)
+	handle.cancel()
+	assert handle.is_cancelled, "the handle should report its cancellation"
+	assert list(handle.receiver) == [], "cancelling should close the receiver"
+	queryable.undeclare()
+
+def check_queued_types():
+	key = "test/python/queued-types"
+	pull = session.declare_pull_subscriber(key)
+	sub = session.declare_subscriber(key, Queue())
+	queryable = session.declare_queryable(key, lambda query: query.reply(zenoh.Sample(query.key_expr, "reply")))
+	time.sleep(0.5)
+	for i in range(3):
+		session.put(key, f"value {i}")
+	received = sub.recv_batch(max_size=3, max_latency=1, timeout=5)
+	pull.pull()
+	received.append(pull.recv(timeout=5))
+	time.sleep(0.5)
+	pull.pull()
+	time.sleep(0.5)
+	received.extend(pull.drain())
+	for sample in received:
+		assert isinstance(sample, zenoh.Sample), f"received a {type(sample)}, not a zenoh.Sample"
+		assert isinstance(sample.kind, SampleKind), f"the sample's kind is a {type(sample.kind)}, not a SampleKind"
+	replies = session.get_list(key)
+	assert len(replies) == 1, f"expected 1 reply, got {len(replies)}"
+	assert all(isinstance(reply, zenoh.Reply) for reply in replies), "get_list should return zenoh.Reply instances"
+	assert replies[0].to_dict()["value"] == "reply", f"unexpected reply {replies[0].to_dict()}"
+	queryable.undeclare()
+	sub.undeclare()
+	pull.undeclare()
+
+def check_scout_stop_from_callback():
+	calls = []
+	def callback(hello):
+		calls.append(hello)
+		# Lets more hellos queue up behind this one before asking to stop.
+		time.sleep(0.5)
+		return False
+	done = []
+	def run():
+		zenoh.scout(callback, what="peer|router", timeout=5)
+		time.sleep(2)
+		done.append(True)
+	t = Thread(target=run)
+	t.start()
+	t.join(timeout=10)
+	assert done, "stopping the scout from its callback should neither hang nor raise"
+	assert len(calls) <= 1, f"the callback was called {len(calls)} times after returning False"
+	class CallableQueue(Queue):
+		def __call__(self, hello):
+			self.put(hello)
+	queue = CallableQueue()
+	scout = zenoh.scout(queue, what="peer|router")
+	assert scout.receiver is queue, "callable handlers should keep their receiver"
+	scout.stop()
+
+def check_admin_space():
+	if session.info().routers_zid():
+		assert session.admin_get(), "connected routers should answer admin space queries"
+		assert session.links(), "the routers should report their links to this session"
+		return
+	for operation in [session.admin_get, session.links]:
+		try:
+			operation()
+		except zenoh.ZError:
+			continue
+		raise AssertionError("querying the admin space without a router should fail")
+
+checks = [
+	check_encoding_suffix,
+	check_reply_kind,
+	check_concurrent_declarations,
+	check_consolidation,
+	check_reply_to_dict_malformed,
+	check_get_encoding_filter,
+	check_selector_parameters,
+	check_matches_encoding,
+	check_pinned_id,
+	check_sample_kind_consistency,
+	check_queue_bound_alias,
+	check_undeclare_keyexpr,
+	check_query_target,
+	check_query_accept,
+	check_config_merge,
+	check_config_from_env_errors,
+	check_default_qos_overrides,
+	check_put_validate_only,
+	check_put_stream_errors,
+	check_shared_publisher,
+	check_publisher_delete_qos,
+	check_keyexpr_errors,
+	check_keyexpr_wildcards,
+	check_keyexpr_strip_prefix,
+	check_subscriber_errors,
+	check_multi_key_subscriber,
+	check_value_equality,
+	check_whatami_hash,
+	check_hello_identity,
+	check_parse_encoding,
+	check_latest_value_cache,
+	check_close_with_children,
+	check_invalid_durations,
+	check_queue_invalid_timeouts,
+	check_recv_batch_arguments,
+	check_query_timeout,
+	check_codec_put,
+	check_payload_slices_lifetime,
+	check_queryable_recv_type,
+	check_get_return_types,
+	check_queued_types,
+	check_scout_stop_from_callback,
+	check_admin_space,
+]
+
+for check in checks:
+	try:
+		check()
+		print(f"{check.__name__}: ok")
+	except Exception as e:
+		print(f"{check.__name__}: failed")
+		traceback.print_exc()
+		errors.append(f"{check.__name__}: {e!r}")
+
+if len(errors):
+	message = f"Found {len(errors)} errors: {(ret+tab) + (ret+tab).join(errors)}"
+	raise Exception(message)
@@ -11,34 +11,126 @@
 # Contributors:
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
-from .zenoh import init_logger, scout as _scout
-from .keyexpr import IntoKeyExpr, IntoSelector, KeyExpr, Selector
+from .zenoh import init_logger, scout as _scout, ZError
+from .keyexpr import IntoKeyExpr, IntoSelector, KeyExpr, Selector, Chunk
 from .config import Config
-from .session import Session, Publisher, Subscriber, PullSubscriber, Info
-from .enums import CongestionControl, Encoding, Priority, QueryConsolidation, QueryTarget, Reliability, SampleKind
-from .value import Hello, Value, IntoValue, IValue, Sample, IntoSample, ZenohId, Timestamp, Reply
-from .closures import Closure, IClosure, IntoClosure, Handler, IHandler, IntoHandler, ListCollector, Queue
+from .session import Session, Publisher, Subscriber, PullSubscriber, QueryHandle, ReplyList, SampleDict, Info
+from .enums import parse_encoding, CongestionControl, Encoding, Priority, QueryConsolidation, QueryTarget, Reliability, SampleKind, WhatAmI, WhatAmIMatcher
+from .value import Hello, Value, IntoValue, IValue, Sample, IntoSample, ZenohId, Timestamp, Reply, register_codec
+from .closures import Closure, IClosure, IntoClosure, Handler, IHandler, IntoHandler, ListCollector, LatestValueCache, Queue
 from .queryable import Queryable, Query
-from typing import Any
+from typing import Any, Optional, Union
 
 def open(*args, **kwargs):
+    """
+    Opens a Zenoh session.
+
+    The configuration may be a `Config`, or a plain `dict` laid out like a JSON configuration file, such as
+    `zenoh.open({"mode": "client", "connect": {"endpoints": ["tcp/localhost:7447"]}})`.
+    Invalid entries in such a `dict` raise a ZError naming the offending field.
+    """
     return Session(*args, **kwargs)
 
+def open_retry(config: Union[Config, Any] = None, attempts: int = 5, delay: float = 1.0, **kwargs):
+    """
+    Opens a Zenoh session like `zenoh.open`, retrying up to `attempts` times, `delay` seconds apart, if it fails.
+
+    This is useful when the session and the router it connects to are started concurrently, as in container deployments.
+    The GIL is released while waiting, and a ZError is raised if all attempts failed.
+    """
+    return Session(config, attempts=attempts, delay=delay, **kwargs)
+
 class Scout:
+    """
+    A handle to an ongoing scouting, which runs until `self.stop()` is called.
+
+    When the scouting's handler is a `Queue`, the discovered hellos may be consumed in a synchronous loop through
+    `self.recv()`, `self.try_recv()` or by iterating on the scout, which ends once the scout is stopped:
+
+    ```python
+    scout = zenoh.scout(zenoh.Queue(), timeout=5)
+    for hello in scout:
+        print(hello)
+    ```
+    """
     def __init__(self, inner, receiver):
         self._inner_ = inner
         self.receiver = receiver
-    
+
+    def recv(self, timeout: float = None) -> Hello:
+        """
+        Waits for the next hello.
+
+        Raises a `TimeoutError` if no hello arrived within `timeout` seconds, or a `StopIteration` if the scout was stopped.
+        """
+        return Hello._upgrade_(self._queue_().get(timeout))
+
+    def try_recv(self) -> Optional[Hello]:
+        "Returns the next hello if one was already received, or `None` otherwise."
+        hello = self._queue_().try_recv()
+        return None if hello is None else Hello._upgrade_(hello)
+
+    def __iter__(self):
+        return (Hello._upgrade_(hello) for hello in self._queue_())
+
+    def _queue_(self) -> Queue:
+        if not isinstance(self.receiver, Queue):
+            raise TypeError("`recv`, `try_recv` and iteration are only available on scouts whose handler is a `zenoh.Queue`")
+        return self.receiver
+
     def stop(self):
-        self._inner_ = None
+        "Stops the scouting, closing its handler."
+        self._inner_.stop()
+
+def scout(handler: IntoHandler[Hello, Any, Any] = None, what: Union[str, WhatAmIMatcher] = None, config: Config = None, timeout=None, dedup: bool = False, multicast_address: str = None, interface: str = None):
+    """
+    Scouts the network for Zenoh nodes, passing each discovered `Hello` to `handler`.
+
+    When no `handler` is provided, the hellos are collected into a list returned by calling `scout.receiver()`.
+
+    `what` selects the roles of the nodes to discover, either as a `WhatAmIMatcher` or as its string form
+    (such as `"peer|router"`). All roles are discovered by default.
+
+    The same node may be discovered several times, for example once per network interface.
+    Setting `dedup` to `True` makes the list-collecting receiver return a single `Hello` per node, keyed by `zid`,
+    with the locators of all its hellos: see `Hello.dedup`.
+
+    `multicast_address` (such as `"224.0.0.224:7446"`) and `interface` (a network interface name or IP address, or `"auto"`)
+    override the `scouting/multicast/address` and `scouting/multicast/interface` settings of `config`, which lets
+    multi-homed machines scout on a specific NIC. A ZError is raised if `multicast_address` isn't a multicast `<ip>:<port>`.
 
-def scout(handler: IntoHandler[Hello, Any, Any] = None, what: str = None, config: Config = None, timeout=None):
-    from threading import Timer
+    When `handler` is a plain callback, its return value tells whether to keep scouting: returning `False` stops the scout,
+    which suits "find one router and stop" flows, while any other value (including `None`) keeps it going.
+    Hellos that were already in flight when the callback returned `False` are discarded.
+    Instances of `IClosure` or `IHandler` are used as is, even if they are callable.
+    """
+    from threading import Event, Timer
     if handler is None:
         handler = ListCollector()
+    stopping = Event()
+    started = []
+    # Closures, handlers and tuples may be callable too, but carry a receiver or a drop that must be kept.
+    if callable(handler) and not isinstance(handler, (IClosure, IHandler, tuple)):
+        callback = handler
+        def handler(hello: Hello):
+            if stopping.is_set():
+                return
+            if callback(hello) is False:
+                stopping.set()
+                if started:
+                    started[0].stop()
     handler = Handler(handler, lambda x: Hello._upgrade_(x))
-    scout = _scout(handler.closure, config, what)
-    scout = Scout(scout, handler.receiver)
+    scout = _scout(handler.closure, config, what, multicast_address, interface)
+    receiver = handler.receiver
+    if dedup:
+        if not callable(receiver):
+            raise TypeError("`dedup` requires a list-collecting handler, such as the default `ListCollector`")
+        receiver = lambda: Hello.dedup(handler.receiver())
+    scout = Scout(scout, receiver)
+    started.append(scout)
+    # The callback may have asked to stop before the scout was built.
+    if stopping.is_set():
+        scout.stop()
     if timeout:
         Timer(timeout, lambda: scout.stop()).start()
     return scout
\ No newline at end of file
@@ -12,18 +12,29 @@
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
 import abc
-from typing import Generic, Callable, Union, Any, TypeVar, Tuple, List
-from threading import Condition, Thread
+from typing import Generic, Callable, Union, Any, TypeVar, Tuple, List, Dict, Optional
+from threading import BoundedSemaphore, Condition, Lock, Thread, current_thread
 from collections import deque
+import logging
 import time
+import warnings
 
 from .zenoh import _Queue
+from .enums import SampleKind
+from .value import Sample
 
 In = TypeVar("In")
 Out = TypeVar("Out")
 Receiver = TypeVar("Receiver")
 CallbackCall = Callable[[In], Out]
 CallbackDrop = Callable[[], None]
+CallbackError = Callable[[In, Exception], None]
+
+_logger_ = logging.getLogger("zenoh")
+
+def _log_error_(value, e: Exception):
+    "The default `on_error` of subscribers, which logs `e` to the `zenoh` logger and skips `value`."
+    _logger_.error("Exception thrown in callback", exc_info=e)
 
 class IClosure(Generic[In, Out]):
     """
@@ -75,8 +86,17 @@ class Closure(IClosure, Generic[In, Out]):
     """
     A Closure is a pair of a `call` function that will be used as a callback,
     and a `drop` function that will be called when the closure is destroyed.
+
+    With `prevent_direct_calls`, values are piped onto a `Queue` and passed to the callback by a dedicated thread.
+    Setting `inflight` instead runs the callback synchronously on the thread that delivers the values, with at most
+    `inflight` concurrent calls: deliveries beyond that limit block, applying backpressure to Zenoh.
+
+    Either way, `type_adaptor` converts each value before it is passed to the callback, and `pending()` reports how many values were delivered to the closure but not yet fully processed by its callback.
+
+    If the callback raises, `on_error` is called with the value and the exception, and the closure carries on with the
+    next values. Without `on_error`, the exception propagates to the caller of `call`.
     """
-    def __init__(self, closure: IntoClosure[In, Out], type_adaptor: Callable[[Any], In] = None, prevent_direct_calls=False):
+    def __init__(self, closure: IntoClosure[In, Out], type_adaptor: Callable[[Any], In] = None, prevent_direct_calls=False, inflight: int = None, on_error: CallbackError = None):
         _call_ = None
         _drop_ = lambda: None
         if isinstance(closure, IHandler):
@@ -91,28 +111,64 @@ class Closure(IClosure, Generic[In, Out]):
             _call_ = closure
         else:
             raise TypeError("Unexpected type as input for zenoh.Closure")
+        if on_error is not None:
+            unguarded = _call_
+            def _call_(value):
+                try:
+                    return unguarded(value)
+                except Exception as e:
+                    on_error(value, e)
         if type_adaptor is not None:
             adapted = lambda *args: _call_(type_adaptor(*args))
         else:
             adapted = _call_
-        if prevent_direct_calls:
+        self._pending_ = 0
+        self._pending_lock_ = Lock()
+        if prevent_direct_calls and inflight is None:
             queue = Queue(128)
             def readqueue():
                 for x in queue:
-                    adapted(*x)
+                    try:
+                        adapted(*x)
+                    finally:
+                        self._add_pending_(-1)
                     x = None
             t = Thread(target=readqueue)
             t.start()
-            self._call_ = lambda *args: queue.put(args)
+            def call(*args):
+                self._add_pending_(1)
+                queue.put(args)
+            self._call_ = call
             def drop():
                 queue.close()
-                t.join()
+                # The handler may undeclare its own entity, dropping the closure from the reading thread itself.
+                if current_thread() is not t:
+                    t.join()
                 _drop_()
             self._drop_ = drop
         else:
-            self._call_ = _call_
+            target = adapted
+            limit = None if inflight is None else BoundedSemaphore(inflight)
+            def call(*args):
+                self._add_pending_(1)
+                try:
+                    if limit is None:
+                        return target(*args)
+                    with limit:
+                        return target(*args)
+                finally:
+                    self._add_pending_(-1)
+            self._call_ = call
             self._drop_ = _drop_
 
+    def _add_pending_(self, delta: int):
+        with self._pending_lock_:
+            self._pending_ += delta
+
+    def pending(self) -> int:
+        "The number of values that were passed to this closure, but whose callback hasn't returned yet."
+        return self._pending_
+
     @property
     def call(self) -> Callable[[In], Out]:
         return self._call_
@@ -129,8 +185,11 @@ class Handler(IHandler, Generic[In, Out, Receiver]):
     Note that the values will be piped onto a `Queue` before being sent to your handler by another Thread unless either:
         a) `input` is already an instance of `Closure` or `Handler` where `input.closure` is an instance of `Closure`
         b) `prevent_direct_calls` is set to `False`
+        c) `inflight` is set, in which case your handler is called synchronously, with at most `inflight` concurrent calls
+
+    `on_error` is passed to the `Closure`, see its documentation.
     """
-    def __init__(self, input: IntoHandler[In, Out, Receiver], type_adaptor: Callable[[Any], In] = None, prevent_direct_calls = True):
+    def __init__(self, input: IntoHandler[In, Out, Receiver], type_adaptor: Callable[[Any], In] = None, prevent_direct_calls = True, inflight: int = None, on_error: CallbackError = None):
         self._receiver_ = None
         if isinstance(input, IHandler):
             self._receiver_ = input.receiver
@@ -147,7 +206,7 @@ class Handler(IHandler, Generic[In, Out, Receiver]):
                 self._closure_ = input
         else:
             self._closure_ = input
-        self._closure_ = Closure(self._closure_, type_adaptor, prevent_direct_calls and not isinstance(self._closure_, Closure))
+        self._closure_ = Closure(self._closure_, type_adaptor, prevent_direct_calls and not isinstance(self._closure_, Closure), inflight, on_error)
 
     @property
     def closure(self) -> IClosure[In, Out]:
@@ -190,6 +249,49 @@ class ListCollector(IHandler[In, None, Callable[[],List[In]]], Generic[In]):
                 return self._vec_
         return wait
 
+class LatestValueCache(IHandler[Sample, None, 'LatestValueCache']):
+    """
+    A handler that retains the latest sample received on each key, for "current state" consumers such as dashboards.
+
+    When used as a subscriber's handler, it provides itself as the receiver: `get_current(key)` returns the latest sample
+    received on `key`, and `snapshot()` all of them, keyed by their key expression's string form. A deletion removes its key
+    from the cache. Reads may happen from any thread while samples are being received.
+
+    The samples must be delivered as `Sample`s, which excludes `declare_subscriber`'s `extract` modes.
+    """
+    def __init__(self):
+        self._samples_: Dict[str, Sample] = dict()
+        self._lock_ = Lock()
+
+    @property
+    def closure(self) -> IClosure[Sample, None]:
+        def call(sample):
+            sample = Sample._upgrade_(sample)
+            key = str(sample.key_expr)
+            with self._lock_:
+                if sample.kind == SampleKind.DELETE():
+                    self._samples_.pop(key, None)
+                else:
+                    self._samples_[key] = sample
+        return Closure((call, lambda: None))
+
+    @property
+    def receiver(self) -> 'LatestValueCache':
+        return self
+
+    def get_current(self, key) -> Optional[Sample]:
+        "Returns the latest sample received on `key` (a `KeyExpr` or its string form), or `None` if there is none."
+        with self._lock_:
+            return self._samples_.get(str(key))
+
+    def snapshot(self) -> Dict[str, Sample]:
+        "Returns a copy of the cache, mapping each key to its latest sample, which later samples won't alter."
+        with self._lock_:
+            return dict(self._samples_)
+
+    def __len__(self) -> int:
+        return len(self._samples_)
+
 class Queue(IHandler[In, None, 'Queue'], Generic[In]):
     """
     A binding for a Rust multi-producer, single-consumer queue implementation.
@@ -197,10 +299,28 @@ class Queue(IHandler[In, None, 'Queue'], Generic[In]):
     When used as a handler, it provides itself as the receiver, and will provide a
     callback that appends elements to the queue.
 
-    Can be bounded by passing a maximum size as `bound`.
+    Whichever entity it is used with (subscriber, query, queryable...), elements are read through the same
+    `try_recv`, `recv` and `recv_timeout` methods, which release the GIL while waiting.
+    Blocking reads still react to signals, so pressing Ctrl-C raises `KeyboardInterrupt` promptly.
+
+    Can be bounded by passing a maximum size as `capacity`, in which case `on_full` decides what happens
+    when an element is put on a full queue:
+    - `"block"` (the default) waits for room to be made, applying backpressure to the producer,
+    - `"drop_latest"` discards the new element,
+    - `"drop_oldest"` discards the oldest element in the queue to make room for the new one.
+
+    The drop policies never block, which makes them suitable for handlers that must not stall
+    Zenoh's threads when their consumer is slow: `dropped_count()` reports how many elements were discarded.
+
+    `bound` is a deprecated alias of `capacity`, which emits a `DeprecationWarning`.
     """
-    def __init__(self, bound: int = None):
-        self._inner_ = _Queue(bound)
+    def __init__(self, capacity: int = None, on_full: str = "block", bound: int = None):
+        if bound is not None:
+            warnings.warn("`bound` is deprecated, use `capacity` instead", DeprecationWarning, stacklevel=2)
+            if capacity is not None:
+                raise TypeError("`bound` and `capacity` can't be passed together")
+            capacity = bound
+        self._inner_ = _Queue(capacity, on_full)
     
     @property
     def closure(self) -> IClosure[In, None]:
@@ -231,8 +351,37 @@ class Queue(IHandler[In, None, 'Queue'], Generic[In]):
         """
         return self._inner_.get(timeout)
     
+    def try_recv(self):
+        """
+        Gets one element from the queue if one is available, returning `None` otherwise.
+
+        This never blocks, and also returns `None` if the queue was closed.
+        """
+        return self._inner_.try_recv()
+
+    def recv(self):
+        """
+        Blocks until an element is available, returning it.
+
+        Raises a `StopIteration` exception if the queue was closed, which happens once the entity
+        it was used as a handler for (subscriber, query...) has finished delivering values.
+        """
+        return self._inner_.recv()
+
+    def recv_timeout(self, timeout: float):
+        """
+        Blocks for up to `timeout` seconds until an element is available, returning it.
+
+        Raises a `TimeoutError` if the timeout ran out, or a `StopIteration` if the queue was closed.
+        """
+        return self._inner_.recv_timeout(timeout)
+
     def close(self):
         return self._inner_.close()
+
+    def dropped_count(self) -> int:
+        "The number of elements that were discarded by the `on_full` policy so far."
+        return self._inner_.dropped_count()
     
     def get_remaining(self, timeout: float = None) -> List[In]:
         """
@@ -242,7 +391,30 @@ class Queue(IHandler[In, None, 'Queue'], Generic[In]):
         Raises a `TimeoutError` if the timeout in seconds provided was exceeded before closing,
         whose `args[0]` will contain the elements that were collected before timing out.
         """
-        return self._inner_.get_remaining()
+        return self._inner_.get_remaining(timeout)
+
+    def recv_batch(self, max_size: int = None, max_latency: float = None, timeout: float = None) -> List[In]:
+        """
+        Waits for up to `timeout` seconds for an element, then returns it in a list along with the elements that follow it,
+        until `max_size` elements are collected.
+
+        With `max_latency`, up to `max_latency` seconds (counted from the arrival of the first element) are spent waiting
+        for the batch to fill up, after which a partial batch is returned. Without it, only the elements that are already
+        queued are added to the batch.
+
+        Raises a `TimeoutError` if no element arrived within `timeout` seconds, or a `StopIteration` if the queue was closed
+        before any element arrived. A batch that was cut short by the queue closing is returned normally.
+        Raises a `ValueError` if `max_size` is less than 1 or `max_latency` isn't a valid duration.
+        """
+        return self._inner_.recv_batch(max_size, max_latency, timeout)
+
+    def drain(self) -> List[In]:
+        """
+        Returns all the values currently in the queue, without waiting for any new ones.
+
+        The returned list is empty if the queue was empty.
+        """
+        return self._inner_.drain()
 
     def __iter__(self):
         return self
@@ -11,8 +11,9 @@
 # Contributors:
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
-from typing import Union
-from .zenoh import _Config
+from typing import Union, List, Dict, Any, Mapping, Optional
+import os
+from .zenoh import _Config, ZError
 import json
 
 class Config(_Config):
@@ -27,12 +28,79 @@ class Config(_Config):
         c = super(Config, Config).from_file(filename)
         return c
     @staticmethod
+    def peer() -> 'Config':
+        "Returns the default configuration, in `peer` mode."
+        return super(Config, Config).peer()
+    @staticmethod
+    def client(endpoints: List[str]) -> 'Config':
+        "Returns a configuration in `client` mode, connecting to the routers listening on `endpoints`."
+        return super(Config, Config).client(endpoints)
+    @staticmethod
+    def router() -> 'Config':
+        "Returns a configuration in `router` mode."
+        return super(Config, Config).router()
+    @staticmethod
     def from_obj(obj):
         """
         Reads the configuration from `obj` as if it was a JSON file.
+
+        Raises a ZError naming the offending field if `obj` is a `dict` holding an invalid entry,
+        such as an unknown key or a value of the wrong type.
         """
-        c = Config.from_json5(json.dumps(obj))
+        try:
+            c = Config.from_json5(json.dumps(obj))
+        except ZError:
+            if not isinstance(obj, dict):
+                raise
+            Config()._locate_error_(obj, "")
+            raise
         return c
+    def _locate_error_(self, obj: Dict[str, Any], prefix: str):
+        for key, value in obj.items():
+            path = f"{prefix}{key}"
+            try:
+                self.insert_json5(path, json.dumps(value))
+            except ZError as e:
+                if isinstance(value, dict) and value:
+                    self._locate_error_(value, f"{path}/")
+                raise ZError(f"Invalid configuration field `{path}`: {e}") from e
+    @staticmethod
+    def from_env(base: 'Config' = None, environ: Mapping[str, str] = None) -> 'Config':
+        """
+        Builds a configuration from environment variables, for deployments that are configured through their environment:
+        - `ZENOH_CONFIG`: the path of a configuration file to start from, as read by `Config.from_file`,
+        - `ZENOH_MODE`: `peer`, `client` or `router`,
+        - `ZENOH_CONNECT`: a comma-separated list of endpoints to connect to, such as `tcp/10.0.0.1:7447,tcp/10.0.0.2:7447`,
+        - `ZENOH_LISTEN`: a comma-separated list of endpoints to listen on,
+        - `ZENOH_ID`: the session's id, as set by `Config.set_id`.
+
+        The variables are applied on top of `base` if provided (which is then modified in place), or of the file named
+        by `ZENOH_CONFIG` otherwise, so that some fields may be set by a file and overridden from the environment.
+        Unset or empty variables are ignored. `environ` defaults to `os.environ`.
+
+        Raises a ZError naming the offending variable if any value is invalid.
+        """
+        environ = os.environ if environ is None else environ
+        def var(name: str) -> Optional[str]:
+            value = environ.get(name, "").strip()
+            return value if value else None
+        def apply(name: str, action):
+            value = var(name)
+            if value is None:
+                return None
+            try:
+                return action(value)
+            except ZError as e:
+                raise ZError(f"Invalid `{name}` environment variable `{value}`: {e}") from e
+        config = base
+        if config is None:
+            config = apply("ZENOH_CONFIG", Config.from_file) or Config()
+        endpoints = lambda value: json.dumps([e.strip() for e in value.split(",") if e.strip()])
+        apply("ZENOH_MODE", lambda value: config.insert_json5("mode", json.dumps(value)))
+        apply("ZENOH_CONNECT", lambda value: config.insert_json5("connect/endpoints", endpoints(value)))
+        apply("ZENOH_LISTEN", lambda value: config.insert_json5("listen/endpoints", endpoints(value)))
+        apply("ZENOH_ID", config.set_id)
+        return config
     @staticmethod
     def from_json5(json: str):
         """
@@ -49,7 +117,46 @@ class Config(_Config):
         in a JSON-serialized form.
         """
         return super().get_json(path)
+
+    def to_json(self) -> str:
+        """
+        Returns the whole configuration as indented JSON, including the default values of the fields that weren't set.
+
+        `str(config)` returns the same.
+        """
+        return super().to_json()
     
+    def set_id(self, id: str):
+        """
+        Pins the identifier of the sessions opened with this configuration, instead of letting Zenoh draw a random one.
+
+        `id` must be the hexadecimal representation of a ZenohId, such as `"a1b2c3d4"`: a ZError is raised if it is malformed
+        or too long. `session.info().zid()` then returns exactly that id, which helps with deterministic tests and log correlation.
+        Note that two sessions with the same id can't be part of the same Zenoh network.
+        """
+        super().set_id(id)
+
+    def merge(self, other: 'Config'):
+        """
+        Applies the fields that were explicitly set in `other` onto this configuration, in place.
+
+        This supports layered configurations, such as a shared base configuration patched per deployment:
+        the fields set by the file, JSON or `dict` `other` was read from, or through its `insert_json5`, override those
+        of `self`, even when they hold their default value, while the fields `other` leaves unset don't. Objects are merged
+        key by key, but arrays (such as `connect/endpoints`) are replaced as a whole rather than concatenated.
+        The configuration returned by `Session.config()` only tracks the fields set through it.
+        """
+        super().merge(other)
+
+    def diff(self, other: 'Config') -> List[str]:
+        """
+        Returns the JSON pointers (such as `/connect/endpoints`) of the fields whose values differ between `self` and `other`,
+        in lexicographic order.
+
+        Like `merge`, arrays are compared as a whole.
+        """
+        return super().diff(other)
+
     def insert_json5(self, path: str, value: str) -> str:
         """
         Inserts the provided value (read from JSON) at the given path in the configuration.
@@ -11,7 +11,8 @@
 # Contributors:
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
-from .zenoh import _Encoding, _SampleKind, _CongestionControl, _Priority, _Reliability, _QueryTarget, _QueryConsolidation
+from typing import List
+from .zenoh import parse_encoding as _parse_encoding, _Encoding, _SampleKind, _CongestionControl, _Priority, _Reliability, _QueryTarget, _QueryConsolidation, _WhatAmI, _WhatAmIMatcher
 
 class Priority(_Priority):
     """
@@ -59,7 +60,12 @@ class Priority(_Priority):
         return super().__ge__(other)
 
 class SampleKind(_SampleKind):
-    "Similar to an HTTP METHOD: only PUT and DELETE are currently supported."
+    """
+    Similar to an HTTP METHOD: only PUT and DELETE are currently supported.
+
+    The same type is accepted by `Session.put`'s and `Session.delete`'s `kind` argument, and returned by `Sample.kind`.
+    Sample kinds compare by value and are hashable, so they may be used as dictionary keys, and `str(kind)` is `"PUT"` or `"DELETE"`.
+    """
     def __new__(cls, inner: _SampleKind):
         return super().__new__(cls, inner)
     @staticmethod
@@ -68,10 +74,16 @@ class SampleKind(_SampleKind):
     @staticmethod
     def DELETE() -> 'SampleKind':
         return SampleKind(_SampleKind.DELETE)
+    @staticmethod
+    def from_str(s: str) -> 'SampleKind':
+        "Parses `\"PUT\"` or `\"DELETE\"`, case-insensitively, raising a ZError for anything else."
+        return SampleKind(_SampleKind.from_str(s))
     def __eq__(self, other) -> bool:
         return super().__eq__(other)
     def __ne__(self, other) -> bool:
         return not self.__eq__(other)
+    def __hash__(self) -> int:
+        return super().__hash__()
 
 class CongestionControl(_CongestionControl):
     """
@@ -100,10 +112,22 @@ class Encoding(_Encoding):
         return super().__new__(cls, inner)
     @staticmethod
     def from_str(s: str) -> 'Encoding':
-        return super(Encoding, Encoding).from_str(s)
+        """
+        Parses `s` into an encoding: the longest known MIME type `s` starts with becomes the encoding's prefix,
+        and the rest of `s` (such as `;schema=v2`) is kept as its suffix.
+        """
+        return Encoding(super(Encoding, Encoding).from_str(s))
+    @property
+    def suffix(self) -> str:
+        "The part of the encoding that follows its known MIME type, such as `;schema=v2`. Empty if there is none."
+        return super().suffix
     def append(self, s: str):
         super().append(s)
     @staticmethod
+    def known() -> List['Encoding']:
+        "Returns Zenoh's whole table of known encodings, in the order of their wire identifiers."
+        return [Encoding(e) for e in _Encoding.known()]
+    @staticmethod
     def EMPTY() -> 'Encoding':
         return Encoding(_Encoding.EMPTY )
     @staticmethod
@@ -171,6 +195,18 @@ class Encoding(_Encoding):
     def __ne__(self, other) -> bool:
         return not self.__eq__(other)
 
+def parse_encoding(mime: str) -> Encoding:
+    """
+    Validates and normalizes a MIME type with optional parameters into an encoding, for content-type handling.
+
+    Unlike `Encoding.from_str`, which accepts any string, this raises a ZError if `mime` isn't of the form
+    `<type>/<subtype>[;<name>=<value>]*`. Surrounding whitespace is dropped, and the type, subtype and parameter names
+    are lowercased, so `parse_encoding(" Application/JSON ; Schema=v2")` is `application/json;schema=v2`, with
+    `Encoding.APP_JSON()` as its known part and `;schema=v2` as its suffix. Parameter values are kept as they are.
+    Types outside of the known encoding table are kept whole, and the empty string parses into `Encoding.EMPTY()`.
+    """
+    return Encoding(_parse_encoding(mime))
+
 class Reliability(_Reliability):
     "Used by subscribers to inform the network of the reliability it wishes to obtain."
     def __new__(cls, inner: _Reliability):
@@ -194,16 +230,24 @@ class Reliability(_Reliability):
         return not self.__eq__(other)
 
 class QueryTarget(_QueryTarget):
+    """
+    Used by `Session.get` to select which of the matching queryables should receive the query.
+
+    `str(target)` returns the variant's name, such as `"ALL_COMPLETE"`.
+    """
     def __new__(cls, inner: _QueryTarget):
         return super().__new__(cls, inner)
     @staticmethod
     def BEST_MATCHING() -> 'QueryTarget':
+        "Queries the matching queryables that are closest, favoring complete ones. This is the default."
         return QueryTarget(_QueryTarget.BEST_MATCHING)
     @staticmethod
     def ALL() -> 'QueryTarget':
+        "Queries every matching queryable."
         return QueryTarget(_QueryTarget.ALL)
     @staticmethod
     def ALL_COMPLETE() -> 'QueryTarget':
+        "Queries only the matching queryables that were declared with `complete=True`."
         return QueryTarget(_QueryTarget.ALL_COMPLETE)
     def __eq__(self, other) -> bool:
         return super().__eq__(other)
@@ -228,4 +272,48 @@ class QueryConsolidation(_QueryConsolidation):
     def __eq__(self, other) -> bool:
         return super().__eq__(other)
     def __ne__(self, other) -> bool:
-        return not self.__eq__(other)
\ No newline at end of file
+        return not self.__eq__(other)
+
+class WhatAmI(_WhatAmI):
+    "The role of a Zenoh node: `router`, `peer` or `client`. Roles compare by value and are hashable."
+    def __new__(cls, inner: _WhatAmI):
+        return super().__new__(cls, inner)
+    @staticmethod
+    def ROUTER() -> 'WhatAmI':
+        return WhatAmI(_WhatAmI.ROUTER)
+    @staticmethod
+    def PEER() -> 'WhatAmI':
+        return WhatAmI(_WhatAmI.PEER)
+    @staticmethod
+    def CLIENT() -> 'WhatAmI':
+        return WhatAmI(_WhatAmI.CLIENT)
+    def __eq__(self, other) -> bool:
+        return super().__eq__(other)
+    def __ne__(self, other) -> bool:
+        return not self.__eq__(other)
+    def __hash__(self) -> int:
+        return super().__hash__()
+    def __str__(self) -> str:
+        return super().__str__()
+
+class WhatAmIMatcher(_WhatAmIMatcher):
+    """
+    A set of node roles, used by `zenoh.scout` to select which kinds of nodes to discover.
+
+    In string form, it is a `|`-separated list of roles, such as `"peer|router"`.
+    """
+    def __new__(cls, inner: _WhatAmIMatcher):
+        return super().__new__(cls, inner)
+    @staticmethod
+    def from_str(s: str) -> 'WhatAmIMatcher':
+        "Parses a `|`-separated list of `peer`, `client` or `router`, raising a ZError if `s` is invalid."
+        return WhatAmIMatcher(super(WhatAmIMatcher, WhatAmIMatcher).from_str(s))
+    @staticmethod
+    def from_list(whatamis: List[WhatAmI]) -> 'WhatAmIMatcher':
+        "Constructs a matcher for the roles in `whatamis`, raising a ZError if it is empty."
+        return WhatAmIMatcher(super(WhatAmIMatcher, WhatAmIMatcher).from_list(whatamis))
+    def matches(self, whatami: WhatAmI) -> bool:
+        "Returns `True` if `whatami` is one of the roles selected by this matcher."
+        return super().matches(whatami)
+    def __str__(self) -> str:
+        return super().__str__()
@@ -11,11 +11,33 @@
 # Contributors:
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
-from typing import Union, Dict
-from .zenoh import _KeyExpr, _Selector
+from typing import Union, Dict, Optional, List
+from .zenoh import _KeyExpr, _Selector, ZError
 
 IntoKeyExpr = Union['KeyExpr', _KeyExpr, str]
 
+class Chunk(str):
+    """
+    A chunk of a key expression, as returned by `KeyExpr.chunks`: a `str` tagged with the kind of chunk it is.
+    """
+    @property
+    def kind(self) -> str:
+        """
+        `"double_wild"` for `**`, `"wild"` for `*`, `"partial"` for chunks containing the `$*` subchunk wildcard,
+        and `"literal"` for chunks without wildcards.
+        """
+        if self == "**":
+            return "double_wild"
+        if self == "*":
+            return "wild"
+        if "$*" in self:
+            return "partial"
+        return "literal"
+    @property
+    def is_wild(self) -> bool:
+        "Whether this chunk contains a wildcard, and may thus match other chunks than itself."
+        return self.kind != "literal"
+
 class KeyExpr(_KeyExpr):
     """
     Zenoh's address space is designed around keys which serve as the names of ressources.
@@ -73,6 +95,57 @@ class KeyExpr(_KeyExpr):
             e = _KeyExpr.autocanonize(expr)
             return KeyExpr(e.as_str())
     
+    @staticmethod
+    def try_from(expr: str) -> Union['KeyExpr', ZError]:
+        """
+        Constructs a key expression from `expr` like the default constructor does,
+        but returns the ZError describing why `expr` is invalid instead of raising it.
+        """
+        k = _KeyExpr.try_from(expr)
+        return KeyExpr(k) if isinstance(k, _KeyExpr) else k
+
+    @staticmethod
+    def has_wildcards(expr: str) -> bool:
+        """
+        Returns `True` if the string `expr` contains any wildcard (`*`, `**` or `$*`), without validating it.
+
+        This lets you tell subscription patterns apart from publishable keys before constructing a key expression.
+        """
+        return _KeyExpr.has_wildcards(expr)
+
+    def is_wild(self) -> bool:
+        """
+        Returns `True` if this key expression contains wildcards,
+        meaning it defines a set of keys rather than a single key.
+        """
+        return super().is_wild()
+
+    def chunks(self) -> List[Chunk]:
+        """
+        Returns the slash-separated chunks of this key expression, in order.
+
+        Each chunk is a `Chunk`, which compares as its string and tells whether it is a wildcard through `kind`
+        and `is_wild`: `"a/*/b$*/**".chunks()` gives `["a", "*", "b$*", "**"]`, whose kinds are
+        `"literal"`, `"wild"`, `"partial"` and `"double_wild"`.
+        Since key expressions are validated and canonized on construction, chunks are never empty.
+
+        Key expressions have no escaped separators: every `/` separates two chunks, so these are exactly the chunks
+        Zenoh matches key expressions by. Canonization merges repeated `**` chunks:
+        `KeyExpr.autocanonize("a/**/**/b").chunks()` gives `["a", "**", "b"]`.
+        """
+        return [Chunk(chunk) for chunk in super().chunks()]
+
+    def strip_prefix(self, prefix: IntoKeyExpr) -> Optional['KeyExpr']:
+        """
+        Returns the remainder of this key expression after `prefix`, or `None` if it doesn't start with `prefix`.
+
+        Only whole chunks are matched: `a/b/c` stripped of `a/b` gives `c`, but `a/bc` doesn't start with `a/b`.
+        `None` is also returned when nothing remains, as when stripping a key expression of itself.
+        Wildcards are compared literally, as in `"a/*/c".strip_prefix("a/*") == "c"`.
+        """
+        remainder = super().strip_prefix(KeyExpr(prefix))
+        return None if remainder is None else KeyExpr(remainder)
+
     def intersects(self, other: 'KeyExpr') -> bool:
         """
         This method returns `True` if there exists at least one key that belongs to both sets
@@ -163,15 +236,28 @@ class Selector(_Selector):
         "The key expression part of the selector."
         return KeyExpr(super().key_expr)
     @property
-    def parameters(self):
-        "The value selector part of the selector."
+    def parameters(self) -> str:
+        """
+        The value selector part of the selector, in its encoded form.
+
+        Assigning it replaces the parameters with the provided string, which must already be percent-encoded:
+        prefer `self.with_parameters` to build parameters from raw values.
+        """
         return super().parameters
     @parameters.setter
-    def set_parameters(self, parameters: str):
-        super().parameters = parameters
+    def parameters(self, parameters: str):
+        _Selector.parameters.__set__(self, parameters)
+    def with_parameters(self, parameters: Dict[str, str]) -> 'Selector':
+        """
+        Returns a copy of this selector whose parameters are replaced by `parameters`.
+
+        Keys and values are percent-encoded like the query section of a URL, so they may contain any character,
+        including `&`, `=` or spaces: `self.with_parameters(p).decode_parameters() == p` always holds.
+        """
+        return Selector._upgrade_(super().with_parameters(parameters))
     def decode_parameters(self) -> Dict[str, str]:
         """
-        Decodes the value selector part of the selector.
+        Decodes the value selector part of the selector, reverting the percent-encoding of its keys and values.
 
         Raises a ZError if some keys were duplicated: duplicated keys are considered undefined behaviour,
         but we encourage you to refuse to process incoming messages with duplicated keys, as they might be
@@ -11,11 +11,13 @@
 # Contributors:
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
-from typing import Dict, Optional
+from typing import Dict, Optional, Iterator, Iterable, List, Tuple
 
 from .zenoh import _Query, _Queryable
-from .keyexpr import KeyExpr, Selector
-from .value import Sample, Value
+from .closures import Queue
+from .keyexpr import KeyExpr, IntoKeyExpr, Selector
+from .value import Sample, Value, IntoValue
+from .enums import Encoding, SampleKind
 
 class Queryable:
     """
@@ -25,14 +27,49 @@ class Queryable:
 
     When constructed through `Session.declare_queryable(session, keyexpr, handler)`, it exposes `handler`'s receiver
     through `self.receiver`.
+
+    When declared without a handler, the queries are delivered to a `Queue`, which lets you serve them in
+    straight-line code, either through `self.recv()` or by iterating on the queryable:
+
+    ```python
+    queryable = session.declare_queryable("demo/example")
+    for query in queryable:
+        query.reply(Sample(query.key_expr, "value"))
+    ```
     """
     def __init__(self, inner: _Queryable, receiver):
         self._inner_ = inner
         self.receiver = receiver
+
+    def recv(self, timeout: float = None) -> 'Query':
+        """
+        Waits for the next query.
+
+        Raises a `TimeoutError` if no query arrived within `timeout` seconds,
+        or a `StopIteration` if the queryable was undeclared.
+        """
+        return self._queue_().get(timeout)
+
+    def __iter__(self) -> Iterator['Query']:
+        return iter(self._queue_())
+
+    def _queue_(self) -> Queue:
+        if not isinstance(self.receiver, Queue):
+            raise TypeError("`recv` and iteration are only available on queryables whose handler is a `zenoh.Queue`")
+        return self.receiver
+    
+    @property
+    def complete(self) -> bool:
+        "Whether this queryable was declared as `complete`, advertising that it holds every key of its key expression."
+        return self._inner_.complete
     
+    def stats(self) -> Dict[str, int]:
+        "Returns the number of `queries_received` by this queryable, counted before they reach its handler."
+        return self._inner_.stats()
+
     def undeclare(self):
         "Stops the queryable."
-        self._inner_ = None
+        self._inner_.undeclare()
 
 class Query(_Query):
     def __new__(cls, inner: _Query):
@@ -57,6 +94,15 @@ class Query(_Query):
         """
         return super().decode_parameters()
     @property
+    def accept(self) -> List[Encoding]:
+        """
+        The reply encodings the querier would like, in order of preference, as set through `Session.get`'s `accept` argument.
+
+        They are read from the comma-separated `_accept` query parameter, ignoring whitespace around entries and empty entries.
+        The list is empty if the querier expressed no preference.
+        """
+        return [Encoding(e) for e in super().accept]
+    @property
     def selector(self) -> Selector:
         """
         The query's selector as a whole.
@@ -74,5 +120,28 @@ class Query(_Query):
         """
         Allows you to reply to a query.
         You may send any amount of replies to a single query, including 0.
+
+        Replies are always sent with Zenoh's default congestion control and priority.
+        """
+        super().reply(sample)
+    def reply_sample(self, sample: Sample):
+        """
+        Replies to the query with an existing `sample`, such as one previously received and stored by a storage.
+
+        The sample is forwarded as is: its key expression, value, encoding, kind and timestamp are all preserved,
+        so replaying stored samples doesn't require rebuilding them field by field.
+        """
+        super().reply_sample(sample)
+    def reply_all(self, items: Iterable[Tuple[IntoKeyExpr, IntoValue]], kind: SampleKind = None, encoding: Encoding = None) -> int:
+        """
+        Replies to the query with a sample for each `(key, value)` pair of `items`, returning the number of replies sent.
+
+        All the samples share the same `kind`, `SampleKind.PUT()` by default, and the same `encoding` if one is provided:
+        otherwise each value is encoded as `Value(value)` would. Keys may be `KeyExpr`s or strings.
+        As for `reply`, the replies' QoS can't be chosen.
+
+        This is meant for storages answering wildcard queries with many matching keys. Replies are sent in order,
+        stopping at the first that fails, in which case a ZError telling which reply failed, and how many were sent
+        before it, is raised.
         """
-        super().reply(sample)
\ No newline at end of file
+        return super().reply_all([(key, Value(value, encoding)) for key, value in items], kind)
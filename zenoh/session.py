@@ -11,15 +11,22 @@
 # Contributors:
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
-from typing import Union, Any, List
+from typing import Union, Any, List, Dict, Callable, Optional, Iterable
+from threading import Lock
+from weakref import WeakSet
+import asyncio
+import json
+import os
+import time
+import warnings
 
-from .zenoh import _Session, _Config, _Publisher, _Subscriber, _PullSubscriber
+from .zenoh import _Session, _Config, _Publisher, _Subscriber, _PullSubscriber, _QueryHandle, ZError
 
 from .keyexpr import KeyExpr, IntoKeyExpr, Selector, IntoSelector
 from .config import Config
-from .closures import IntoHandler, Handler, Receiver
+from .closures import IntoHandler, Handler, Receiver, Queue, Closure, LatestValueCache, _log_error_
 from .enums import *
-from .value import IntoValue, Value, Sample, Reply, ZenohId
+from .value import IntoValue, IValue, Value, Sample, Reply, ZenohId, Timestamp, FILE_ENCODINGS, _codec_for_
 from .queryable import Queryable, Query
 
 
@@ -29,12 +36,53 @@ class Publisher:
     def __init__(self, p: _Publisher):
         self._inner_ = p
 
-    def put(self, value: IntoValue, encoding: Encoding = None):
-        "An optimised version of `session.put(self.key_expr, value, encoding=encoding)"
-        self._inner_.put(Value(value, encoding))
+    def put(self, value: IntoValue, encoding: Encoding = None, return_size: bool = False) -> Optional[int]:
+        """
+        An optimised version of `session.put(self.key_expr, value, encoding=encoding)`
+
+        With `return_size=True`, returns the number of payload bytes that were published, as `Session.put` does.
+
+        `bytes`, `str` and other buffer-protocol objects (such as `bytearray` or `memoryview`) are sent directly,
+        without having to wrap them into a `Value`: `pub.put(b"hello")` and `pub.put("world")` just work.
+
+        When `encoding` is omitted and the publisher was declared with a default encoding, the `value` is serialized
+        as usual and sent with the publisher's encoding instead. Values that carry their own encoding keep it.
+        """
+        if encoding is None and isinstance(value, (bytes, str, bytearray, memoryview)):
+            size = self._inner_.put(value)
+        else:
+            if encoding is None and not isinstance(value, IValue):
+                default = self.encoding
+                if default is not None and _codec_for_(default) is not None:
+                    value = Value.encode(value, default)
+                elif default is not None:
+                    value = Value.autoencode(value)
+                    value.encoding = default
+            size = self._inner_.put(Value(value, encoding))
+        return size if return_size else None
+
+    async def put_async(self, value: IntoValue, encoding: Encoding = None):
+        """
+        The asyncio counterpart of `self.put`, which doesn't block the event loop.
+
+        The put is offloaded to the running loop's default executor, and the GIL is released while the value is handed
+        over to Zenoh, so other threads and the loop keep running. It must thus be awaited from within a running loop,
+        such as one started by `asyncio.run`. Consecutive awaited calls are sent in order.
+
+        An executor is used rather than a native coroutine because a put only blocks while it is queued for transmission,
+        which doesn't warrant bridging Zenoh's async runtime into asyncio through an extra dependency such as `pyo3-asyncio`.
+        """
+        await asyncio.get_running_loop().run_in_executor(None, self.put, value, encoding)
+
+    def delete(self, attachment: bytes = None, timestamp: Timestamp = None):
+        """
+        An optimised version of `session.delete(self.key_expr)`, using the publisher's `priority` and `congestion_control`.
 
-    def delete(self):
-        "An optimised version of `session.delete(self.key_expr)"
+        `attachment` and `timestamp` aren't supported by the underlying Zenoh API yet: passing either raises a `NotImplementedError`.
+        """
+        for option, value in (("attachment", attachment), ("timestamp", timestamp)):
+            if value is not None:
+                raise NotImplementedError(f"`Publisher.delete` doesn't support the `{option}` option yet")
         self._inner_.delete()
 
     @property
@@ -42,9 +90,38 @@ class Publisher:
         "This `Publisher`'s key expression"
         return KeyExpr(self._inner_.key_expr)
 
+    @property
+    def priority(self) -> Priority:
+        "The priority of this publisher's puts and deletes, as set by `Session.declare_publisher` or the session's defaults."
+        return Priority(self._inner_.priority)
+
+    @property
+    def congestion_control(self) -> CongestionControl:
+        "The congestion control of this publisher's puts and deletes, as set by `Session.declare_publisher` or the session's defaults."
+        return CongestionControl(self._inner_.congestion_control)
+
+    @property
+    def encoding(self) -> Optional[Encoding]:
+        "The encoding applied by `self.put` when none is provided, as set by `Session.declare_publisher`. May be None."
+        encoding = self._inner_.encoding
+        return None if encoding is None else Encoding(encoding)
+
+    def stats(self) -> Dict[str, int]:
+        "Returns the number of `puts_sent` and `deletes_sent` through this publisher."
+        return self._inner_.stats()
+
+    def is_valid(self) -> bool:
+        """
+        Returns `False` once the publisher was undeclared.
+
+        All the handles to a same publisher share its state: after undeclaring it through one of them, `put` and `delete`
+        raise a ZError on every handle, while `key_expr`, `encoding` and `stats` remain available.
+        """
+        return self._inner_.is_valid()
+
     def undeclare(self):
         "Stops the publisher."
-        self._inner_ = None
+        self._inner_.undeclare()
 
 
 class Subscriber:
@@ -57,13 +134,86 @@ class Subscriber:
     through `self.receiver`.
     """
 
-    def __init__(self, s: _Subscriber, receiver=None):
+    def __init__(self, s: _Subscriber, receiver=None, closure: Closure = None):
         self._subscriber_ = s
         self.receiver = receiver
+        self._closure_ = closure
+
+    def pending(self) -> int:
+        """
+        The number of samples this subscriber received whose handler hasn't returned yet.
+
+        When this keeps growing, the handler is slower than the incoming rate and is the bottleneck: see `inflight` in
+        `Session.declare_subscriber` to apply backpressure instead. Handlers that only store the samples, such as a `Queue`,
+        return immediately, so this stays close to 0 for them.
+        """
+        return 0 if self._closure_ is None else self._closure_.pending()
+
+    @property
+    def key_expr(self) -> KeyExpr:
+        """
+        The key expression this subscriber was declared with, in its validated form.
+
+        Raises a TypeError for subscribers declared on several key expressions: use `self.key_exprs` instead.
+        """
+        if isinstance(self._subscriber_, _SubscriberGroup):
+            raise TypeError("this subscriber was declared on several key expressions: use `key_exprs` instead")
+        return KeyExpr(self._subscriber_.key_expr)
+
+    @property
+    def key_exprs(self) -> List[KeyExpr]:
+        "The key expressions this subscriber was declared with."
+        if isinstance(self._subscriber_, _SubscriberGroup):
+            return [KeyExpr(s.key_expr) for s in self._subscriber_._subscribers_]
+        return [self.key_expr]
+
+    def recv_batch(self, max_size: int = None, max_latency: float = None, timeout: float = None) -> List[Sample]:
+        """
+        Receives the samples delivered to this subscriber's `Queue` by batches, which lets high-rate streams be processed
+        by windows without paying for a Python call per sample.
+
+        Waits for up to `timeout` seconds for a first sample, then keeps collecting samples for up to `max_latency` seconds
+        or until `max_size` samples are collected, whichever comes first: see `Queue.recv_batch`.
+        The samples are returned as they were delivered to the queue, which depends on `declare_subscriber`'s `extract`.
+        """
+        if not isinstance(self.receiver, Queue):
+            raise TypeError("`recv_batch` is only available on subscribers whose handler is a `zenoh.Queue`")
+        return self.receiver.recv_batch(max_size, max_latency, timeout)
+
+    def get_current(self, key: IntoKeyExpr) -> Optional[Sample]:
+        "Returns the latest sample received on `key` by this subscriber's `LatestValueCache`: see `LatestValueCache.get_current`."
+        return self._cache_().get_current(key)
+
+    def snapshot(self) -> Dict[str, Sample]:
+        "Returns the latest sample received on each key by this subscriber's `LatestValueCache`: see `LatestValueCache.snapshot`."
+        return self._cache_().snapshot()
+
+    def _cache_(self) -> LatestValueCache:
+        if not isinstance(self.receiver, LatestValueCache):
+            raise TypeError("`get_current` and `snapshot` are only available on subscribers whose handler is a `zenoh.LatestValueCache`")
+        return self.receiver
+
+    def stats(self) -> Dict[str, int]:
+        "Returns the number of `samples_received` by this subscriber, counted before they reach its handler."
+        return self._subscriber_.stats()
 
     def undeclare(self):
         "Undeclares the subscription"
-        self._subscriber_ = None
+        self._subscriber_.undeclare()
+
+
+class _SubscriberGroup:
+    "Several subscribers sharing a single handler, which behave as a single `_Subscriber`."
+
+    def __init__(self, subscribers: List[_Subscriber]):
+        self._subscribers_ = subscribers
+
+    def stats(self) -> Dict[str, int]:
+        return {"samples_received": sum(s.stats()["samples_received"] for s in self._subscribers_)}
+
+    def undeclare(self):
+        for s in self._subscribers_:
+            s.undeclare()
 
 
 class PullSubscriber:
@@ -76,12 +226,20 @@ class PullSubscriber:
     through `self.receiver`.
 
     Calling `self.pull()` will prompt the Zenoh network to send a new sample when available.
+
+    When declared without a handler, the samples are delivered to a `Queue` that is read through `self.recv()` and
+    `self.drain()`, letting you consume the pulled samples from the thread that called `self.pull()`.
     """
 
     def __init__(self, s: _PullSubscriber, receiver=None):
         self._subscriber_ = s
         self.receiver = receiver
 
+    @property
+    def key_expr(self) -> KeyExpr:
+        "The key expression this subscriber was declared with, in its validated form."
+        return KeyExpr(self._subscriber_.key_expr)
+
     def pull(self):
         """
         Prompts the Zenoh network to send a new sample if available.
@@ -89,29 +247,187 @@ class PullSubscriber:
         """
         self._subscriber_.pull()
 
+    def recv(self, timeout: float = None) -> Sample:
+        """
+        Waits for a sample to be delivered to this subscriber's queue.
+
+        `self.pull()` returns as soon as the pull request is sent, before the network has answered it:
+        this method is used to wait for the samples that request will yield.
+
+        Raises a `TimeoutError` if no sample arrived within `timeout` seconds,
+        or a `StopIteration` if the subscriber was undeclared.
+        """
+        return self._queue_().get(timeout)
+
+    def drain(self) -> List[Sample]:
+        """
+        Returns the samples that have already been delivered to this subscriber's queue, without waiting.
+
+        Since samples arrive asynchronously after `self.pull()`, calling `self.drain()` immediately after it
+        may return fewer samples than the pull will eventually provide: use `self.recv(timeout)` to wait for them.
+        """
+        return self._queue_().drain()
+
+    def _queue_(self) -> Queue:
+        if not isinstance(self.receiver, Queue):
+            raise TypeError("`recv` and `drain` are only available on pull subscribers whose handler is a `zenoh.Queue`")
+        return self.receiver
+
+    def stats(self) -> Dict[str, int]:
+        "Returns the number of `samples_received` by this subscriber, counted before they reach its handler."
+        return self._subscriber_.stats()
+
     def undeclare(self):
         "Undeclares the subscription"
-        self._subscriber_ = None
+        self._subscriber_.undeclare()
+
+
+class QueryHandle:
+    """
+    The handle to a query, returned by `Session.get_handle`.
+
+    It exposes the `handler`'s receiver as `self.receiver`. For convenience, iterating on the handle, calling it,
+    or accessing its attributes is forwarded to that receiver, so `for reply in session.get_handle(selector, Queue())`
+    works as it does on `session.get`'s return value.
+
+    Calling `self.cancel()` stops the delivery of further replies and closes the receiver, letting you abort a
+    long-running query once it has given you enough replies, for example before `break`ing out of a loop on it.
+    """
+
+    def __init__(self, inner: _QueryHandle, receiver):
+        self._inner_ = inner
+        self.receiver = receiver
+
+    def cancel(self):
+        """
+        Stops delivering replies to the handler, and closes it.
+
+        Replies already delivered to the receiver remain available. Cancelling an already finalized query has no effect.
+        """
+        self._inner_.cancel()
+
+    @property
+    def is_cancelled(self) -> bool:
+        "Whether `self.cancel()` was called."
+        return self._inner_.is_cancelled
+
+    @property
+    def is_finalized(self) -> bool:
+        "Whether Zenoh finalized the query, either because all of its replies were received or because it timed out."
+        return self._inner_.is_finalized
+
+    @property
+    def timed_out(self) -> bool:
+        """
+        Whether the query was finalized by its timeout rather than by receiving all of its replies, in which case some replies
+        may be missing. This is known once the query is finalized after its deadline passed, and is `False` until then.
+        """
+        return self._inner_.timed_out
+
+    def __iter__(self):
+        return iter(self.receiver)
+
+    def __call__(self, *args, **kwargs):
+        return self.receiver(*args, **kwargs)
+
+    def __getattr__(self, name):
+        return getattr(self.receiver, name)
+
+
+class ReplyList(list):
+    """
+    The list of replies returned by `Session.get_list`.
+
+    `completed` tells whether the query was finalized by receiving all of its replies, rather than by timing out,
+    in which case the list may be missing some replies.
+    """
+    def __init__(self, replies: Iterable[Reply] = (), completed: bool = True):
+        super().__init__(replies)
+        self.completed = completed
+
+class SampleDict(dict):
+    """
+    The samples grouped by key returned by `Session.get_dict`.
+
+    `completed` tells whether the query was finalized by receiving all of its replies, rather than by timing out,
+    in which case some keys or samples may be missing.
+    """
+    def __init__(self, samples: Dict[str, List[Sample]] = None, completed: bool = True):
+        super().__init__(samples or {})
+        self.completed = completed
+
+def _sample_sort_key_(sort_by: str) -> Callable[[Sample], Any]:
+    if sort_by == "key":
+        return lambda sample: str(sample.key_expr)
+    if sort_by == "timestamp":
+        return lambda sample: (sample.timestamp is not None, sample.timestamp)
+    raise ValueError(f"`sort_by` must be one of \"key\", \"timestamp\" or None, got {sort_by!r}")
 
 
 class Session(_Session):
     """
     A Zenoh Session, the core interraction point with a Zenoh network.
+
+    Before any networking starts, the configuration is checked for the most common misconfigurations, such as
+    endpoints that aren't of the form `<protocol>/<address>` or have a malformed `<host>:<port>` address, or a `client`
+    that has no way to find routers. All the problems found are reported at once in a single ZError.
+    These checks are only syntactic: whether an endpoint's protocol is supported is left to Zenoh.
+    Pass `validate=False` to skip them.
+
+    Opening the session is attempted up to `attempts` times, waiting `delay` seconds between attempts,
+    before raising a ZError: see `zenoh.open_retry`.
     """
-    def __new__(cls, config: Union[Config, Any] = None):
-        if config is None:
-            return super().__new__(cls)
-        elif isinstance(config, _Config):
-            return super().__new__(cls, config)
-        else:
-            return super().__new__(cls, Config.from_obj(config))
+    def __new__(cls, config: Union[Config, Any] = None, validate: bool = True, attempts: int = 1, delay: float = 1.0):
+        if config is not None and not isinstance(config, _Config):
+            config = Config.from_obj(config)
+        return super().__new__(cls, config, validate, attempts, delay)
+
+    def __init__(self, *args, **kwargs):
+        self._children_ = WeakSet()
+        self._children_lock_ = Lock()
+        self._closed_ = False
+
+    def _adopt_(self, child):
+        "Tracks `child`, an entity just declared from this session, so that `close` may undeclare it."
+        with self._children_lock_:
+            if not self._closed_:
+                self._children_.add(child)
+                return child
+        child.undeclare()
+        raise ZError("Attempted to declare an entity on a closed session")
 
     def put(self, keyexpr: IntoKeyExpr, value: IntoValue, encoding=None,
             priority: Priority = None, congestion_control: CongestionControl = None,
-            sample_kind: SampleKind = None):
+            kind: SampleKind = None, validate_only: bool = False, return_size: bool = False,
+            sample_kind: SampleKind = None) -> Optional[int]:
         """
         Sends a value over Zenoh.
+
+        Raises a ZError if `kind` is `SampleKind.DELETE()`: deletions must go through `delete`, so that subscribers
+        never receive a deletion carrying a value.
+
+        `sample_kind` is a deprecated alias of `kind`, which emits a `DeprecationWarning`. It used to be ignored,
+        and is now checked like `kind`.
+
+        With `validate_only=True`, the key expression, value, encoding and QoS options are all checked and converted
+        exactly as for an actual put, raising the same errors, but nothing is published. This lets tools validate
+        a put before committing to it.
+
+        With `return_size=True`, returns the number of payload bytes that were published (or would have been, with
+        `validate_only`), as measured on the serialized payload, which spares throughput accounting from recomputing it.
+
+        Otherwise returns `None`: the timestamp Zenoh may assign to the sample isn't reported back.
+
+        Attachments aren't supported, so application metadata such as a producer-side timestamp must be carried in the payload.
+
+        Returning only means the sample was queued for transmission: Zenoh offers no acknowledgement or flush,
+        so a process exiting right after a put may lose it.
         """
+        if sample_kind is not None:
+            warnings.warn("`sample_kind` is deprecated, use `kind` instead", DeprecationWarning, stacklevel=2)
+            if kind is not None:
+                raise TypeError("`sample_kind` and `kind` can't be passed together")
+            kind = sample_kind
         value = Value(value, encoding)
         keyexpr = KeyExpr(keyexpr)
         kwargs = dict()
@@ -119,9 +435,62 @@ class Session(_Session):
             kwargs['priority'] = priority
         if congestion_control is not None:
             kwargs['congestion_control'] = congestion_control
-        if sample_kind is not None:
-            kwargs['sample_kind'] = sample_kind
-        return super().put(keyexpr, value, **kwargs)
+        if kind is not None:
+            kwargs['kind'] = kind
+        if validate_only:
+            kwargs['validate_only'] = True
+        size = super().put(keyexpr, value, **kwargs)
+        return size if return_size else None
+
+    def put_stream(self, keyexpr: IntoKeyExpr, values: Iterable[IntoValue],
+                   priority: Priority = None, congestion_control: CongestionControl = None) -> int:
+        """
+        Publishes every value yielded by `values` on `keyexpr`, in order, returning how many were published.
+
+        All the puts share the same QoS options, and the GIL is released while each value is handed over to Zenoh,
+        so generators may keep producing values from other threads. This is meant for bulk ingestion, such as ETL
+        tools that feed Zenoh from a file or a database cursor. Values are serialized as `put` does.
+
+        The stream stops at the first error, whether it is raised by the iterator or by a put: a ZError is then raised,
+        whose message tells how many values were published before it.
+        """
+        kwargs = dict()
+        if priority is not None:
+            kwargs['priority'] = priority
+        if congestion_control is not None:
+            kwargs['congestion_control'] = congestion_control
+        return super().put_stream(KeyExpr(keyexpr), (Value(value) for value in values), **kwargs)
+
+    def put_file(self, keyexpr: IntoKeyExpr, path: Union[str, os.PathLike], encoding: Encoding = None, **kwargs):
+        """
+        Publishes the contents of the file at `path` on `keyexpr`.
+
+        Unless an `encoding` is provided, it is picked from the file's extension according to `zenoh.value.FILE_ENCODINGS`,
+        defaulting to `Encoding.APP_OCTET_STREAM()`. Additional keyword arguments are forwarded to `put`.
+
+        The file is read whole into memory: on the receiving end, `Sample.to_file` writes a payload back to disk.
+        """
+        with open(path, "rb") as f:
+            payload = f.read()
+        if encoding is None:
+            mime = FILE_ENCODINGS.get(os.path.splitext(path)[1].lower())
+            encoding = Encoding.APP_OCTET_STREAM() if mime is None else Encoding.from_str(mime)
+        return self.put(keyexpr, payload, encoding=encoding, **kwargs)
+
+    def set_default_qos(self, priority: Priority = None, congestion_control: CongestionControl = None):
+        """
+        Sets the priority and congestion control applied by `put`, `put_stream`, `delete` and `declare_publisher`
+        when they aren't given one explicitly, for applications that use a single QoS profile throughout.
+
+        Explicit arguments still take precedence. Each call only replaces the defaults it is given: the settings left to `None`
+        keep their previous default, if any, and Zenoh's own defaults otherwise. Use `reset_default_qos` to clear them.
+        Queries have no QoS settings, so `get` isn't affected.
+        """
+        super().set_default_qos(priority, congestion_control)
+
+    def reset_default_qos(self):
+        "Clears the defaults set through `set_default_qos`, so that Zenoh's own defaults apply again."
+        super().reset_default_qos()
 
     def config(self) -> Config:
         """
@@ -129,36 +498,156 @@ class Session(_Session):
 
         Note that in Python specifically, the config you passed to the session becomes the result of this
         function if you passed one, letting you keep using it.
+
+        The returned configuration is the one actually in effect, including the defaults Zenoh filled in:
+        `print(session.config())` shows it as JSON, which helps finding out why a session doesn't connect.
         """
         return super().config()
 
     def delete(self, keyexpr: IntoKeyExpr,
-               priority: Priority = None, congestion_control: CongestionControl = None):
+               priority: Priority = None, congestion_control: CongestionControl = None,
+               allow_wildcard: bool = False, kind: SampleKind = None):
         """
         Deletes a value.
+
+        Raises a ZError if `kind` is `SampleKind.PUT()`: use `put` to publish values.
+
+        Since deleting a key expression containing wildcards (such as `demo/**`) deletes every matching key,
+        a ZError is raised for such key expressions unless `allow_wildcard` is set to `True`.
         """
         keyexpr = KeyExpr(keyexpr)
         kwargs = dict()
+        if allow_wildcard:
+            kwargs['allow_wildcard'] = allow_wildcard
+        if kind is not None:
+            kwargs['kind'] = kind
         if priority is not None:
             kwargs['priority'] = priority
         if congestion_control is not None:
             kwargs['congestion_control'] = congestion_control
         return super().delete(keyexpr, **kwargs)
 
-    def get(self, selector: IntoSelector, handler: IntoHandler[Reply, Any, Receiver], consolidation: QueryConsolidation = None, target: QueryTarget = None, value: IntoValue = None) -> Receiver:
+    def get(self, selector: IntoSelector, handler: IntoHandler[Reply, Any, Receiver] = None, consolidation: QueryConsolidation = None, target: QueryTarget = None, value: IntoValue = None, timeout: float = None, encoding: Encoding = None, accept: Union[Encoding, List[Encoding]] = None, parameters: Union[str, Dict[str, str]] = None, deadline: float = None) -> Receiver:
         """
         Emits a query.
+
+        `parameters` lets you pass the selector's parameters separately from its key expression, either as a dictionary
+        of raw values (percent-encoded like `Selector.with_parameters` does) or as an already encoded string:
+        `session.get("demo/**", parameters={"_time": "[..]"})` queries `demo/**?_time=%5B..%5D`.
+        A ZError is raised if `selector` already has parameters of its own.
+
+        Returns the `handler`'s receiver. If no `handler` is provided, the replies are delivered to a `Queue`, which is returned.
+
+        The query is finalized, closing the `handler`, once all replies have been received or after `timeout` seconds.
+
+        `deadline` is an alternative to `timeout` for queries that must complete by a fixed wall-clock time, such as several
+        queries fanned out with a shared deadline: it is an absolute time in seconds since the Unix epoch, as returned by
+        `time.time()`, from which the remaining duration is passed as the timeout. If both are set, the earliest applies.
+        A ZError is raised without emitting the query if `deadline` is already past.
+
+        To cancel the query before it is finalized, or to find out whether it timed out, use `get_handle` instead.
+
+        When `encoding` is provided, replies whose sample has a different encoding (suffix included) are discarded before
+        being converted into Python objects, and thus never reach the `handler`. Error replies are always kept.
+        Note that this is client-side filtering: queryables still compute and send every reply, so it doesn't save bandwidth.
+
+        `accept` tells queryables which reply encodings you'd like, in order of preference, letting them negotiate the encoding
+        they reply with through `Query.accept`. It is sent as the `_accept` query parameter, whose value is the comma-separated
+        list of encodings. It is only a hint: queryables are free to ignore it.
+        """
+        return self.get_handle(selector, handler, consolidation, target, value, timeout, encoding, accept, parameters, deadline).receiver
+
+    def get_handle(self, selector: IntoSelector, handler: IntoHandler[Reply, Any, Receiver] = None, consolidation: QueryConsolidation = None, target: QueryTarget = None, value: IntoValue = None, timeout: float = None, encoding: Encoding = None, accept: Union[Encoding, List[Encoding]] = None, parameters: Union[str, Dict[str, str]] = None, deadline: float = None) -> QueryHandle:
+        """
+        Emits a query exactly as `get` does, with the same arguments, but returns a `QueryHandle` rather than the `handler`'s receiver.
+
+        The handle exposes the receiver as `handle.receiver`, and lets you cancel the query before it is finalized,
+        or find out whether it timed out.
         """
-        handler = Handler(handler, lambda x: Reply(x))
+        selector = Selector(selector)
+        if parameters is not None:
+            if selector.parameters:
+                raise ZError(f"`parameters` were passed, but `{selector}` already has parameters")
+            if isinstance(parameters, dict):
+                selector = selector.with_parameters(parameters)
+            else:
+                selector = Selector(f"{selector.key_expr}?{parameters}")
+        handler = Handler(Queue() if handler is None else handler, lambda x: Reply(x))
         kwargs = dict()
         if consolidation is not None:
-            kwargs["conconsolidation"] = consolidation
+            kwargs["consolidation"] = consolidation
         if target is not None:
             kwargs["target"] = target
         if value is not None:
             kwargs["value"] = Value(value)
-        super().get(Selector(selector), handler.closure, **kwargs)
-        return handler.receiver
+        if deadline is not None:
+            remaining = deadline - time.time()
+            if remaining <= 0:
+                raise ZError(f"The query's deadline passed {-remaining:.3f}s ago")
+            timeout = remaining if timeout is None else min(timeout, remaining)
+        if timeout is not None:
+            kwargs["timeout"] = timeout
+        if encoding is not None:
+            kwargs["encoding"] = encoding
+        if accept is not None:
+            kwargs["accept"] = accept if isinstance(accept, list) else [accept]
+        inner = super().get(selector, handler.closure, **kwargs)
+        return QueryHandle(inner, handler.receiver)
+
+    def get_prefix(self, prefix: IntoKeyExpr, handler: IntoHandler[Reply, Any, Receiver] = None, **kwargs) -> Receiver:
+        """
+        Queries every key under `prefix`, as `self.get(f"{prefix}/**", handler, **kwargs)` would, `prefix` itself included.
+
+        A ZError is raised if `prefix` already contains wildcards. Since `prefix` is validated as a key expression first,
+        trailing slashes are rejected rather than producing an invalid `prefix//**`.
+        """
+        prefix = KeyExpr(prefix)
+        if prefix.is_wild():
+            raise ZError(f"`{prefix}` already contains wildcards: use `get` to query it as is")
+        return self.get(prefix / "**", handler, **kwargs)
+
+    def get_list(self, selector: IntoSelector, timeout: float = None, sort_by: str = None, **kwargs) -> ReplyList:
+        """
+        Emits a query like `get` does, and waits for it to be finalized, returning the replies it was answered with.
+
+        The returned `ReplyList` is a `list` whose `completed` attribute is `False` if the query timed out before
+        receiving all of its replies, in which case the list may be partial: see `QueryHandle.timed_out`.
+
+        Replies are returned in the order they arrived in, unless `sort_by` is `"key"` or `"timestamp"`, in which case
+        the successful replies are sorted by the key or timestamp of their sample (samples without a timestamp first),
+        followed by the error replies. Sorting only applies to the buffered `get_list` and `get_dict`: replies are always
+        passed to `get`'s handler as they arrive. Additional keyword arguments are forwarded to `get`.
+        """
+        handle = self.get_handle(selector, Queue(), timeout=timeout, **kwargs)
+        replies = list(handle.receiver)
+        if sort_by is not None:
+            key = _sample_sort_key_(sort_by)
+            replies = sorted((r for r in replies if r.is_ok), key=lambda r: key(r.ok)) + [r for r in replies if not r.is_ok]
+        return ReplyList(replies, not handle.timed_out)
+
+    def get_dict(self, selector: IntoSelector, timeout: float = None, sort_by: str = None, **kwargs) -> SampleDict:
+        """
+        Emits a query like `get` does, and waits for it to be finalized, returning the samples it was answered with
+        grouped by key, as a `SampleDict`: a `dict` whose `completed` attribute is set as for `get_list`.
+
+        Error replies are discarded. Additional keyword arguments are forwarded to `get`.
+        With `sort_by="key"`, the dictionary's keys are sorted, while with `sort_by="timestamp"`, the samples of each key
+        are sorted by timestamp: see `get_list`.
+        """
+        results = dict()
+        handle = self.get_handle(selector, Queue(), timeout=timeout, **kwargs)
+        for reply in handle.receiver:
+            try:
+                sample = reply.ok
+            except ZError:
+                continue
+            results.setdefault(str(sample.key_expr), []).append(sample)
+        if sort_by == "key":
+            results = dict(sorted(results.items()))
+        elif sort_by is not None:
+            key = _sample_sort_key_(sort_by)
+            results = {k: sorted(samples, key=key) for k, samples in results.items()}
+        return SampleDict(results, not handle.timed_out)
 
     def declare_keyexpr(self, keyexpr: IntoKeyExpr) -> KeyExpr:
         """
@@ -168,36 +657,57 @@ class Session(_Session):
         """
         return KeyExpr(super().declare_keyexpr(KeyExpr(keyexpr)))
 
-    def declare_queryable(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Query, Any, Any], complete: bool = None):
+    def undeclare_keyexpr(self, keyexpr: KeyExpr):
+        """
+        Releases a key expression previously returned by `self.declare_keyexpr`, which must no longer be used afterwards.
+
+        Processes that keep declaring new key expressions should undeclare those they are done with, so that their
+        session doesn't keep growing: the number of currently declared key expressions is reported by `self.stats()`.
+        Raises a ZError if `keyexpr` wasn't declared on this session.
+        """
+        super().undeclare_keyexpr(keyexpr)
+
+    def declare_queryable(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Query, Any, Any] = None, complete: bool = None):
         """
         Declares a queryable, which will receive queries intersecting with `keyexpr`.
 
         These queries are passed to the `handler` as instances of the `Query`class, which lets you respond when applicatble.
 
         The `handler`'s receiver is returned as the `receiver` field of the return value.
+        If no `handler` is provided, the queries are delivered to a `Queue`, read through the returned queryable's
+        `recv()` method or by iterating on it.
 
         IMPORTANT: due to how RAII and Python work, you MUST bind this function's return value to a variable in order for it to function as expected.
         This is because as soon as a value is no longer referenced in Python, that value's destructor will run, which will undeclare your queryable, stopping it immediately.
         """
+        if handler is None:
+            handler = Queue()
         handler = Handler(handler, lambda x: Query(x))
         kwargs = dict()
         if complete is not None:
             kwargs['complete'] = complete
         inner = super().declare_queryable(KeyExpr(keyexpr), handler.closure, **kwargs)
-        return Queryable(inner, handler.receiver)
+        return self._adopt_(Queryable(inner, handler.receiver))
 
-    def declare_publisher(self, keyexpr: IntoKeyExpr, priority: Priority = None, congestion_control: CongestionControl = None):
+    def declare_publisher(self, keyexpr: IntoKeyExpr, priority: Priority = None, congestion_control: CongestionControl = None, encoding: Encoding = None):
         """
         Declares a publisher, which you may use to send values repeatedly onto a same key expression.
+
+        `encoding` sets the publisher's default encoding, used by `Publisher.put` calls that don't provide one:
+        a JSON telemetry publisher may thus be declared with `encoding=Encoding.APP_JSON()` once and for all.
+
+        `priority` and `congestion_control` are the only QoS settings of publishers: reliability is chosen by subscribers.
         """
         kwargs = dict()
+        if encoding is not None:
+            kwargs['encoding'] = encoding
         if priority is not None:
             kwargs['priority'] = priority
         if congestion_control is not None:
             kwargs['congestion_control'] = congestion_control
-        return Publisher(super().declare_publisher(KeyExpr(keyexpr), **kwargs))
+        return self._adopt_(Publisher(super().declare_publisher(KeyExpr(keyexpr), **kwargs)))
 
-    def declare_subscriber(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Sample, Any, Any], reliability: Reliability = None) -> Subscriber:
+    def declare_subscriber(self, keyexpr: Union[IntoKeyExpr, List[IntoKeyExpr]], handler: IntoHandler[Sample, Any, Any], reliability: Reliability = None, inflight: int = None, extract: str = None, on_error: Callable[[Any, Exception], None] = None) -> Subscriber:
         """
         Declares a subscriber, which will receive any published sample with a key expression intersecting `keyexpr`.
 
@@ -205,39 +715,185 @@ class Session(_Session):
 
         The `handler`'s receiver is returned as the `receiver` field of the return value.
 
+        `keyexpr` may also be a list of key expressions, in which case a single logical subscriber is declared for all of them:
+        the `handler` receives the samples of all the key expressions (use `sample.key_expr` to tell which it was published on),
+        and is only closed once all of them are undeclared by calling the returned subscriber's `undeclare()`.
+        Note that a sample whose key matches several of the key expressions is received once for each of them.
+
+        By default, the samples are queued and passed to a callback `handler` by a dedicated thread, so a slow handler
+        lets samples pile up. Setting `inflight` runs the handler synchronously on Zenoh's delivery threads instead,
+        with at most `inflight` concurrent calls, which applies backpressure to Zenoh rather than queueing.
+        Either way, `Subscriber.pending()` reports how many samples are waiting for the handler.
+
+        Throughput-bound handlers that don't need the samples' metadata may set `extract` to `"bytes"` to be passed
+        each sample's payload as `bytes`, or to `"value"` to be passed its `Value`, which skips building the `Sample`
+        objects and their key expressions and timestamps. `"sample"`, the default, passes `Sample`s.
+
+        If the `handler` raises while processing a sample, `on_error` is called with that sample and the exception,
+        so that failures may be logged or routed per sample, and the subscriber carries on with the next samples.
+        Without `on_error`, the exception is logged to the `"zenoh"` logger and the sample is skipped.
+
+        To keep track of the current state of the matching keys, use a `LatestValueCache` as the `handler`,
+        then read it through the returned subscriber's `get_current(key)` and `snapshot()`. As it needs whole samples,
+        combining it with an `extract` other than `"sample"` raises a ValueError.
+
         IMPORTANT: due to how RAII and Python work, you MUST bind this function's return value to a variable in order for it to function as expected.
         This is because as soon as a value is no longer referenced in Python, that value's destructor will run, which will undeclare your subscriber, deactivating the subscription immediately.
         """
-        handler = Handler(handler, lambda x: Sample._upgrade_(x))
+        adaptors = {None: Sample._upgrade_, "sample": Sample._upgrade_, "value": Value._upgrade_, "bytes": None}
+        if extract not in adaptors:
+            raise ValueError(f"`extract` must be one of \"sample\", \"value\" or \"bytes\", got {extract!r}")
+        if isinstance(handler, LatestValueCache) and extract not in (None, "sample"):
+            raise ValueError(f"`LatestValueCache` needs samples, and can't be used with `extract={extract!r}`")
+        handler = Handler(handler, adaptors[extract], inflight=inflight, on_error=_log_error_ if on_error is None else on_error)
         kwargs = dict()
         if reliability is not None:
             kwargs['reliability'] = reliability
-        s = super().declare_subscriber(KeyExpr(keyexpr), handler.closure, **kwargs)
-        return Subscriber(s, handler.receiver)
-
-    def declare_pull_subscriber(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Sample, Any, Any], reliability: Reliability = None) -> PullSubscriber:
+        if extract is not None:
+            kwargs['extract'] = extract
+        if not isinstance(keyexpr, list):
+            s = super().declare_subscriber(KeyExpr(keyexpr), handler.closure, **kwargs)
+            return self._adopt_(Subscriber(s, handler.receiver, handler.closure))
+        if not keyexpr:
+            raise ValueError("`keyexpr` must hold at least one key expression")
+        keyexprs = [KeyExpr(k) for k in keyexpr]
+        closure = handler.closure
+        remaining = [len(keyexprs)]
+        lock = Lock()
+        def drop():
+            with lock:
+                remaining[0] -= 1
+                last = remaining[0] == 0
+            if last:
+                closure.drop()
+        shared = Closure((closure.call, drop))
+        subscribers = []
+        try:
+            for k in keyexprs:
+                subscribers.append(super().declare_subscriber(k, shared, **kwargs))
+        except:
+            for s in subscribers:
+                s.undeclare()
+            # The failed declaration dropped its closure already, but the ones that weren't attempted never will.
+            for _ in range(len(keyexprs) - len(subscribers) - 1):
+                drop()
+            raise
+        return self._adopt_(Subscriber(_SubscriberGroup(subscribers), handler.receiver, closure))
+
+    def declare_pull_subscriber(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Sample, Any, Any] = None, reliability: Reliability = None) -> PullSubscriber:
         """
         Declares a pull-mode subscriber, which will receive a single published sample with a key expression intersecting `keyexpr` any time its `pull` method is called.
 
         These samples are passed to the `handler`'s closure as instances of the `Sample` class.
 
         The `handler`'s receiver is returned as the `receiver` field of the return value.
+
+        If no `handler` is provided, a `Queue` is used, and the pulled samples may be read through the returned
+        subscriber's `recv` and `drain` methods.
         """
+        if handler is None:
+            handler = Queue()
         handler = Handler(handler, lambda x: Sample._upgrade_(x))
         kwargs = dict()
         if reliability is not None:
             kwargs['reliability'] = reliability
         s = super().declare_pull_subscriber(KeyExpr(keyexpr), handler.closure, **kwargs)
-        return PullSubscriber(s, handler.receiver)
+        return self._adopt_(PullSubscriber(s, handler.receiver))
+
+    def admin_get(self, path: str = "*", timeout: float = 1.0) -> Dict[str, Any]:
+        """
+        Queries the admin space of the routers this session is connected to, returning a dictionary mapping the key
+        of each reply to its JSON-decoded payload (or to its string form if it isn't JSON).
+
+        Only routers (`zenohd`) run an admin space, which they expose under `@/router/<zid>`: `path` is the key expression
+        to query relative to `@/router/`, such as `*` for the state of each router. A ZError is raised if the session isn't
+        connected to any router, rather than returning an empty result. Replies are awaited for up to `timeout` seconds.
+        """
+        if not self.routers_zid():
+            raise ZError("The admin space is only served by routers, and this session isn't connected to any")
+        results = dict()
+        for reply in self.get(f"@/router/{path}", Queue(), timeout=timeout):
+            try:
+                sample = reply.ok
+            except ZError:
+                continue
+            payload = sample.payload.decode()
+            try:
+                results[str(sample.key_expr)] = json.loads(payload)
+            except ValueError:
+                results[str(sample.key_expr)] = payload
+        return results
+
+    def links(self, timeout: float = 1.0) -> List[str]:
+        """
+        Returns the links between this session and the routers it is connected to, as reported by the `sessions`
+        of each router's admin space: see `admin_get`, which raises a ZError if there is no such router.
+        """
+        zid = str(self.zid())
+        links = []
+        for state in self.admin_get("*", timeout).values():
+            for transport in state.get("sessions", []) if isinstance(state, dict) else []:
+                if transport.get("peer") == zid:
+                    links.extend(transport.get("links", []))
+        return links
+
+    def ping(self, timeout: float = 1.0, require_router: bool = False) -> bool:
+        """
+        A cheap liveness check, meant for readiness and liveness probes.
+
+        The session is considered healthy when it answers, within `timeout` seconds, a loopback query sent to a private
+        queryable it declares for the occasion: this proves that the session is open and that its query machinery
+        isn't stalled, but not that remote nodes are reachable, since loopback queries don't go through the network.
+
+        With `require_router=True`, the session must additionally be connected to at least one router,
+        as reported by `self.info().routers_zid()`.
+
+        Never raises: any failure, including a closed session, is reported as `False`.
+        """
+        try:
+            if require_router and not self.info().routers_zid():
+                return False
+            key = f"@/session/{self.zid()}/ping/{id(self)}"
+            queryable = self.declare_queryable(key, lambda query: query.reply(Sample(query.key_expr, "pong")))
+            try:
+                replies = self.get(key, Queue(), timeout=timeout)
+                return any(reply.is_ok for reply in replies)
+            finally:
+                queryable.undeclare()
+        except Exception:
+            return False
 
     def close(self):
-        "Closes the Session"
-        pass
+        """
+        Closes the Session, first undeclaring the publishers, subscribers and queryables declared
+        from it that are still alive, so that they are torn down before the session rather than through it.
+
+        Their handlers are closed as when undeclaring them yourself, and further declarations on the session raise a ZError.
+        The session's own network resources are only released once this object is garbage collected.
+        Closing a closed session does nothing.
+        """
+        with self._children_lock_:
+            self._closed_ = True
+            children = list(self._children_)
+            self._children_.clear()
+        for child in children:
+            child.undeclare()
 
     def info(self):
         "Returns an accessor for informations about this Session"
         return Info(self)
 
+    def stats(self) -> Dict[str, int]:
+        """
+        Returns counters of this session's activity since it was opened: `puts_sent`, `deletes_sent`, `gets_sent`
+        and `replies_received` (summed over all queries), as well as the number of `keyexprs_declared` through
+        `declare_keyexpr` that haven't been undeclared yet.
+
+        Subscribers, pull subscribers, publishers and queryables expose their own counters through their `stats` method.
+        The counters are maintained with atomics, so polling them periodically to chart throughput is cheap.
+        """
+        return super().stats()
+
 
 class Info:
     def __init__(self, session: _Session):
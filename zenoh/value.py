@@ -12,13 +12,27 @@
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
 import abc
-from typing import Union, Tuple, Optional, List
+import os
+from typing import Union, Tuple, Optional, List, Dict, Callable, Any
 import json
+from threading import Lock
 
-from .enums import Encoding, SampleKind
-from .zenoh import _Value, _Encoding, _Sample, _SampleKind, _Reply, _ZenohId, _Timestamp, _Hello
+from .enums import Encoding, SampleKind, WhatAmI
+from .zenoh import _Value, _Encoding, _Sample, _SampleKind, _Reply, _ZenohId, _Timestamp, _Hello, _PayloadSlice
 from .keyexpr import KeyExpr, IntoKeyExpr
 
+def _view_(part: Union[bytes, _PayloadSlice]) -> memoryview:
+    """
+    Wraps a slice returned by `_Value.payload_slices` in a read-only `memoryview`.
+
+    `_PayloadSlice`s export their memory as read-only buffers, which the views reference. Builds against the stable ABI
+    can't export buffers, and copy each slice instead.
+    """
+    try:
+        return memoryview(part)
+    except TypeError:
+        return memoryview(bytes(part))
+
 class IValue:
     "The IValue interface exposes how to recover a value's payload in a binary-serialized format, as well as that format's encoding."
     @property
@@ -33,11 +47,95 @@ class IValue:
 
 IntoValue = Union[IValue, bytes, str, int, float, object]
 
+def _decode_text(payload: bytes) -> str:
+    return payload.decode()
+
+DEFAULT_DECODERS: Dict[str, Callable[[bytes], Any]] = {
+    "text/plain": _decode_text,
+    "text/html": _decode_text,
+    "text/xml": _decode_text,
+    "text/css": _decode_text,
+    "text/csv": _decode_text,
+    "text/javascript": _decode_text,
+    "application/xml": _decode_text,
+    "application/xhtml+xml": _decode_text,
+    "application/sql": _decode_text,
+    "application/properties": _decode_text,
+    "application/x-www-form-urlencoded": _decode_text,
+    "application/json": json.loads,
+    "text/json": json.loads,
+    "application/integer": int,
+    "application/float": float,
+}
+"""
+The decoders used by `Value.decode`, keyed by the MIME type of the encoding they apply to (the encoding without its suffix).
+
+Applications may add or replace entries to change how `Value.decode` behaves globally.
+"""
+
+_codecs_: Dict[str, Tuple[Callable[[Any], bytes], Callable[[bytes], Any]]] = {}
+_codecs_lock_ = Lock()
+
+def register_codec(encoding: Union[Encoding, str], encode: Callable[[Any], bytes], decode: Callable[[bytes], Any]):
+    """
+    Registers a codec for `encoding`, such as `"application/x-msgpack"` or `"application/protobuf;type=Point"`.
+
+    `encode` is then used to serialize the objects that aren't `bytes` whenever they are converted into values of
+    this encoding: by `Value.encode(obj, encoding)`, but also by `Value(obj, encoding)` and thus by `Session.put`,
+    `Publisher.put` (including with a publisher's default encoding) and the other functions that take an `encoding`.
+    `Value.decode()` uses `decode` to deserialize the payloads of values that have this encoding.
+    Codecs are matched against the complete encoding first, suffix included, then against its MIME type only,
+    and take precedence over `DEFAULT_DECODERS`. Registering a codec for an encoding replaces the previous one.
+
+    The registry is shared by the whole process, and may be safely updated from any thread.
+    """
+    with _codecs_lock_:
+        _codecs_[str(encoding)] = (encode, decode)
+
+def _lookup_codec_(encoding: str, mime: str):
+    with _codecs_lock_:
+        codec = _codecs_.get(encoding)
+        return _codecs_.get(mime) if codec is None else codec
+
+def _codec_for_(encoding: Encoding):
+    "Returns the codec registered for `encoding`, or `None` if there is none."
+    name = str(encoding)
+    return _lookup_codec_(name, name[:len(name) - len(encoding.suffix)])
+
+FILE_ENCODINGS: Dict[str, str] = {
+    ".txt": "text/plain",
+    ".html": "text/html",
+    ".htm": "text/html",
+    ".css": "text/css",
+    ".csv": "text/csv",
+    ".js": "text/javascript",
+    ".xml": "application/xml",
+    ".xhtml": "application/xhtml+xml",
+    ".sql": "application/sql",
+    ".properties": "application/properties",
+    ".json": "application/json",
+    ".jpg": "image/jpeg",
+    ".jpeg": "image/jpeg",
+    ".png": "image/png",
+    ".gif": "image/gif",
+}
+"""
+The encodings used by `Session.put_file`, keyed by lowercase file extension.
+
+Files whose extension isn't listed are published as `application/octet-stream`.
+"""
+
 class Value(_Value, IValue):
     """
     A Value is a pair of a binary payload, and a mime-type-like encoding string.
     
     When constructed with `encoding==None`, the encoding will be selected depending on the payload's type.
+    Otherwise, `payload` must either be `bytes`, used as is, or be serializable by the codec registered for `encoding`
+    through `register_codec`.
+
+    Two values are equal if both their payloads and their encodings are, so `Value("1") != Value(1)`:
+    the same bytes may mean different things under different encodings. Values are hashable accordingly,
+    but hashing (like comparing) reads the whole payload, which is costly for large values.
     """
     def __new__(cls, payload: IntoValue, encoding: Encoding=None):
         if encoding is None:
@@ -46,7 +144,7 @@ class Value(_Value, IValue):
             return Value.autoencode(payload)
         else:
             if not isinstance(payload, bytes):
-                raise TypeError("`encoding` was passed, but `payload` is not of type `bytes`")
+                return Value.encode(payload, encoding)
             return Value.new(payload, encoding)
     
     @staticmethod
@@ -62,11 +160,86 @@ class Value(_Value, IValue):
         if isinstance(value, float):
             return Value.new(f"{value}".encode(), Encoding.APP_FLOAT())
         return Value.new(json.dumps(value).encode(), Encoding.APP_JSON())
+
+    @staticmethod
+    def encode(value: Any, encoding: Union[Encoding, str]) -> 'Value':
+        """
+        Serializes `value` into a value of the given `encoding`, using the codec registered for it through `register_codec`.
+
+        `bytes` are used as the payload as is when no codec is registered, while other types raise a `TypeError`.
+        """
+        if not isinstance(encoding, Encoding):
+            encoding = Encoding.from_str(encoding)
+        codec = _codec_for_(encoding)
+        if codec is not None:
+            return Value.new(codec[0](value), encoding)
+        if isinstance(value, bytes):
+            return Value.new(value, encoding)
+        raise TypeError(f"No codec was registered for encoding `{encoding}`, only `bytes` can be encoded with it")
     
     @staticmethod
     def new(payload: bytes, encoding: Encoding = None) -> 'Value':
         return Value._upgrade_(_Value.new(payload, encoding))
 
+    @staticmethod
+    def from_str(s: str, encoding: Encoding = None) -> 'Value':
+        "Constructs a value holding `s` encoded in UTF-8, with `Encoding.TEXT_PLAIN()` unless another `encoding` is provided."
+        return Value._upgrade_(_Value.from_str(s, encoding))
+
+    @staticmethod
+    def from_bytes(payload: bytes, encoding: Encoding = None) -> 'Value':
+        "Constructs a value holding `payload`, with `Encoding.APP_OCTET_STREAM()` unless another `encoding` is provided."
+        return Value._upgrade_(_Value.from_bytes(payload, encoding))
+
+    def as_bytes(self) -> bytes:
+        "Returns the value's payload, regardless of its encoding."
+        return super().as_bytes()
+
+    def payload_slices(self) -> List[memoryview]:
+        """
+        Returns the payload as a list of `memoryview`s, one per fragment of the buffer it was received in.
+
+        Large payloads are often received in several fragments: processing or writing them one slice at a time
+        avoids assembling them into a single contiguous `bytes`, as accessing `self.payload` would.
+        The views are read-only and borrow Zenoh's buffer without copying it (except with builds against the stable ABI,
+        where each slice is copied). Each of them keeps the part of the buffer it points into alive,
+        so they remain valid after this value has been dropped, or its payload replaced.
+        Once `self.payload` has been accessed, the payload is a single slice.
+        """
+        return [_view_(part) for part in super().payload_slices()]
+
+    def sha256(self) -> str:
+        """
+        Returns the hexadecimal SHA-256 digest of the payload, computed over Zenoh's buffer without copying it into `bytes`.
+
+        The encoding isn't part of the digest, so two values with the same payload but different encodings have the same one.
+        """
+        return super().sha256()
+
+    def fingerprint(self) -> int:
+        """
+        Returns a fast, non-cryptographic 64-bit hash of the payload, suitable for deduplicating values within a process.
+
+        Unlike `sha256`, it isn't stable across releases of these bindings, and must thus not be persisted or shared.
+        """
+        return super().fingerprint()
+
+    def __len__(self) -> int:
+        "The payload's size in bytes, read without converting the payload into `bytes`."
+        return super().__len__()
+
+    def is_empty(self) -> bool:
+        "Returns `True` if the payload is empty."
+        return super().is_empty()
+
+    def as_string(self) -> str:
+        """
+        Returns the value's payload decoded as UTF-8, regardless of its encoding.
+
+        Raises a ZError if the payload isn't valid UTF-8.
+        """
+        return super().as_string()
+
     @property
     def payload(self) -> bytes:
         return super().payload
@@ -83,6 +256,38 @@ class Value(_Value, IValue):
     def encoding(self, encoding: Encoding):
         super().with_encoding(encoding)
 
+    @property
+    def encoding_suffix(self) -> str:
+        "A shortcut to `self.encoding.suffix`, which applications may use to version their schemas."
+        return super().encoding_suffix
+
+    def decode(self, decoders: Dict[str, Callable[[bytes], Any]] = None) -> Any:
+        """
+        Returns the natural Python representation of the value, depending on its encoding.
+
+        The decoder is looked up by the encoding's MIME type (ignoring its suffix), first in `decoders` if provided,
+        then among the codecs registered through `register_codec` (which may also match the suffix),
+        and finally in `zenoh.value.DEFAULT_DECODERS`, which maps:
+        - text encodings (such as `text/plain` or `application/xml`) to `str`,
+        - `application/json` and `text/json` to the `dict`, `list` or scalar they hold,
+        - `application/integer` to `int` and `application/float` to `float`.
+
+        Values whose encoding has no decoder are returned as their raw `bytes` payload.
+        """
+        encoding = str(self.encoding)
+        mime = encoding[:len(encoding) - len(self.encoding_suffix)]
+        decoder = None
+        if decoders is not None:
+            decoder = decoders.get(mime)
+        if decoder is None:
+            codec = _lookup_codec_(encoding, mime)
+            decoder = None if codec is None else codec[1]
+        if decoder is None:
+            decoder = DEFAULT_DECODERS.get(mime)
+        if decoder is None:
+            return self.payload
+        return decoder(self.payload)
+
     @staticmethod
     def _upgrade_(inner: _Value) -> 'Value':
         if inner is None:
@@ -124,35 +329,80 @@ IntoSample = Union[_Sample, Tuple[IntoKeyExpr, IntoValue, SampleKind], Tuple[Key
 class Sample(_Sample):
     """
     A KeyExpr-Value pair, annotated with the kind (PUT or DELETE) of publication used to emit it and a timestamp.
+
+    `sample.key_expr` is the key the sample was published on, which may be any of the keys matched by a subscriber's
+    key expression, and `sample.value` is its payload along with its encoding.
     """
     def __new__(cls, key: IntoKeyExpr, value: IntoValue, kind: SampleKind = None, timestamp: Timestamp = None):
         kind = _SampleKind.PUT if kind is None else kind
         return Sample._upgrade_(super().new(KeyExpr(key), Value(value), kind, timestamp))
     @property
     def key_expr(self) -> KeyExpr:
-        "The sample's key expression"
+        "The key this sample was published on, as a `KeyExpr`."
         return KeyExpr(super().key_expr)
     @property
     def value(self) -> Value:
-        "The sample's value"
+        "The sample's `Value`, holding both its payload and its encoding."
         return Value._upgrade_(super().value)
     @property
     def payload(self) -> bytes:
         "A shortcut to `self.value.payload`"
         return super().payload
+    def payload_slices(self) -> List[memoryview]:
+        """
+        A shortcut to `self.value.payload_slices()`, which lets large payloads be processed incrementally.
+
+        The views borrow the sample's buffer without copying it, and keep it alive for as long as they are.
+        """
+        return [_view_(part) for part in super().payload_slices()]
     @property
     def encoding(self) -> Encoding:
         "A shortcut to `self.value.encoding`"
         return Encoding(super().encoding)
     @property
     def kind(self) -> SampleKind:
-        "The sample's kind"
+        """
+        The sample's kind.
+
+        Samples received as replies keep the kind they were replied with, so storages that keep tombstones
+        may answer queries on deleted keys with `SampleKind.DELETE()` samples.
+        """
         return SampleKind(super().kind)
     @property
     def timestamp(self) -> Optional[Timestamp]:
         "The sample's  timestamp. May be None."
         ts = super().timestamp
         return None if ts is None else Timestamp._upgrade_(ts)
+    def to_file(self, path: Union[str, 'os.PathLike']):
+        """
+        Writes the sample's payload to the file at `path`, replacing its contents if it already exists.
+
+        The payload is written as is, whatever its encoding: this is the receiving end of `Session.put_file`.
+        """
+        with open(path, "wb") as f:
+            f.write(self.payload)
+    def matches_encoding(self, encoding: Encoding) -> bool:
+        """
+        Returns `True` if the sample's encoding is `encoding`, suffix included.
+
+        `application/json;schema=v2` thus matches neither `application/json` nor `application/json;schema=v1`,
+        which lets handlers route samples by schema version. An empty suffix is the same as no suffix.
+        """
+        return super().matches_encoding(encoding)
+    @property
+    def source_id(self) -> Optional[ZenohId]:
+        """
+        The identifier of the session that originally published the sample, if Zenoh carried it.
+
+        Together with `source_sn`, it uniquely identifies a publication across a mesh, even when it is replayed
+        or received through several routes: unlike the timestamp, it is the right key for exactly-once processing.
+        """
+        zid = super().source_id
+        return None if zid is None else ZenohId._upgrade_(zid)
+    @property
+    def source_sn(self) -> Optional[int]:
+        "The sequence number of the sample within the publications of `source_id`, if Zenoh carried it."
+        return super().source_sn
     @staticmethod
     def _upgrade_(inner: _Sample) -> 'Sample':
         if isinstance(inner, Sample):
@@ -167,6 +417,16 @@ class Reply(_Reply):
         "The reply's sender's id."
         return ZenohId._upgrade_(super().replier_id)
     @property
+    def received_at(self) -> float:
+        """
+        The time at which this reply was received, in seconds since the Unix Epoch, as measured by the local clock.
+
+        This is different from `self.ok.timestamp`, which is stamped by the replier (if at all) and represents
+        the time of the sample's publication: to measure a query's round-trip time, compare `received_at` with
+        the value of `time.time()` taken just before calling `Session.get`.
+        """
+        return super().received_at
+    @property
     def ok(self) -> Sample:
         """
         The reply's inner data sample.
@@ -182,9 +442,47 @@ class Reply(_Reply):
         Raises a ZError if the `self` is actually an `ok` reply.
         """
         return Value._upgrade_(super().err)
+    @property
+    def value(self) -> Value:
+        """
+        The reply's value, whichever its kind: the value of its sample for `ok` replies, or its error value for `err` replies.
+
+        Use `self.is_ok` to tell them apart when needed.
+        """
+        return Value._upgrade_(super().value)
+    def matches_encoding(self, encoding: Encoding) -> bool:
+        "Returns `True` if this is an `ok` reply whose sample's encoding is `encoding`: see `Sample.matches_encoding`."
+        return super().matches_encoding(encoding)
+    @property
+    def is_ok(self) -> bool:
+        "Whether this is an `ok` reply, holding a sample, rather than an `err` reply, holding an error value."
+        return super().is_ok
+    def to_dict(self) -> Dict[str, Any]:
+        """
+        Returns the reply as a plain `dict`, convenient for debugging and structured logging:
+        `{"key": str, "value": ..., "kind": str, "ok": bool, "replier": str}`.
+
+        `value` is decoded with `Value.decode`, falling back to the raw `bytes` payload if decoding fails
+        (e.g. malformed JSON or non-UTF-8 text). For `err` replies, `value` holds the decoded error value,
+        while `key` and `kind` are `None`.
+        """
+        def decode(value: Value) -> Any:
+            try:
+                return value.decode()
+            except ValueError:
+                return value.payload
+        if self.is_ok:
+            sample = self.ok
+            key, value, kind = str(sample.key_expr), decode(sample.value), str(sample.kind)
+        else:
+            key, value, kind = None, decode(self.err), None
+        return {"key": key, "value": value, "kind": kind, "ok": self.is_ok, "replier": str(self.replier_id)}
 
 class Hello(_Hello):
     "Represents a single Zenoh node discovered through scouting."
+    def __new__(cls, whatami: WhatAmI, zid: str = None, locators: List[str] = None):
+        "Builds a hello by hand, such as to feed a scouting callback in tests. Raises a ZError if `zid` or a locator is invalid."
+        return Hello._upgrade_(super().new(whatami, zid, locators or []))
     @property
     def zid(self) -> ZenohId:
         "The node's Zenoh UUID."
@@ -198,6 +496,38 @@ class Hello(_Hello):
     def locators(self) -> List[str]:
         "The locators through which this node may be adressed."
         return super().locators
+    def to_dict(self) -> Dict[str, Any]:
+        """
+        Returns the hello as a JSON-serializable dictionary: `{"zid": str, "whatami": str, "locators": [str]}`.
+
+        Hellos compare and hash by `zid`, so discovered nodes may also be accumulated in a `set` across scouting callbacks.
+        Hellos without a `zid` are only equal to themselves.
+        """
+        return super().to_dict()
+    def merge(self, other: 'Hello') -> 'Hello':
+        "Returns a copy of `self` whose locators are extended with those of `other` it didn't already have."
+        return Hello._upgrade_(super().merge(other))
+    @staticmethod
+    def dedup(hellos: List['Hello']) -> List['Hello']:
+        """
+        Deduplicates `hellos` by `zid`, merging the locators of hellos that come from the same node.
+
+        Hellos are deduplicated following the same rule as `==`: those without a `zid` are all kept.
+        The result is in the order in which each node was first seen.
+        """
+        result = []
+        indices = dict()
+        for hello in hellos:
+            zid = hello.zid
+            if zid is None:
+                result.append(hello)
+            elif str(zid) in indices:
+                index = indices[str(zid)]
+                result[index] = result[index].merge(hello)
+            else:
+                indices[str(zid)] = len(result)
+                result.append(hello)
+        return result
     @staticmethod
     def _upgrade_(inner: _Hello) -> 'Sample':
         if isinstance(inner, Hello):